@@ -1,7 +1,8 @@
 use bevy::prelude::{
-    AppBuilder, IntoSystem, Plugin, Query, Res, TextureAtlasSprite, Time, Timer, Commands,
+    AppBuilder, IntoSystem, Plugin, Query, Res, TextureAtlasSprite, Timer, Commands,
     Entity, EventReader,
 };
+use crate::clock::GameClock;
 use crate::dialogue::{AnimationStartEvent, AnimationEndEvent};
 use rand::Rng;
 use std::collections::HashMap;
@@ -50,6 +51,13 @@ pub struct Transitions {
     current: Option<String>,
 }
 
+impl Transitions {
+    /// The named animation playing instead of the default, if any.
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+}
+
 pub struct Animation {
     definition: AnimationDefinition,
     frames: Vec<u32>,
@@ -115,11 +123,11 @@ fn handle_animation_transitions(
 }
 
 fn animate_sprite_system(
-    time: Res<Time>,
+    clock: Res<GameClock>,
     mut query: Query<(&mut Timer, &mut TextureAtlasSprite, &mut Animation)>,
 ) {
     for (mut timer, mut sprite, mut animation) in query.iter_mut() {
-        timer.tick(time.delta());
+        timer.tick(clock.delta());
         if timer.finished() {
             let (frame, state) = match &animation.definition {
                 AnimationDefinition::Simple => {
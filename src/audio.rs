@@ -1,13 +1,16 @@
 use bevy::prelude::{
-    Plugin, Res, AppBuilder, IntoSystem, Handle, EventReader, Local,
+    Plugin, Res, AppBuilder, IntoSystem, Handle, EventReader, Local, Query, Added,
 };
 use bevy_kira_audio::{Audio, AudioChannel, AudioSource};
+use crate::clock::GameClock;
+use crate::dialogue::TrackId;
 use std::ops::Deref;
 
-
+#[derive(Clone, Debug, PartialEq)]
 pub enum Channel {
     Music,
-    Dialogue,
+    /// Each dialogue track speaks on its own channel
+    Dialogue(TrackId),
 }
 
 pub struct PlayAudioEvent {
@@ -23,7 +26,7 @@ struct MusicChannel(AudioChannel);
 
 impl Default for MusicChannel {
     fn default() -> MusicChannel {
-        return MusicChannel(AudioChannel::new("music".to_string()))
+        MusicChannel(AudioChannel::new("music".to_string()))
     }
 }
 
@@ -32,24 +35,15 @@ impl Deref for MusicChannel {
 
     #[inline]
     fn deref(&self) -> &AudioChannel {
-        return &self.0
+        &self.0
     }
 }
-struct DialogueChannel(AudioChannel);
 
-impl Default for DialogueChannel {
-    fn default() -> DialogueChannel {
-        return DialogueChannel(AudioChannel::new("dialogue".to_string()))
-    }
-}
-
-impl Deref for DialogueChannel {
-    type Target = AudioChannel;
-
-    #[inline]
-    fn deref(&self) -> &AudioChannel {
-        return &self.0
+fn dialogue_channel(track: &TrackId) -> AudioChannel {
+    if *track == TrackId::main() {
+        return AudioChannel::new("dialogue".to_string())
     }
+    AudioChannel::new(format!("dialogue_{}", track.0))
 }
 
 fn events(
@@ -57,23 +51,93 @@ fn events(
     mut ev_stop: EventReader<StopAudioEvent>,
     audio: Res<Audio>,
     music_channel: Local<MusicChannel>,
-    dialogue_channel: Local<DialogueChannel>,
 ) {
     for PlayAudioEvent { channel, handle } in ev_play.iter() {
         match channel {
             Channel::Music => audio.play_looped_in_channel(handle.clone(), &music_channel),
-            Channel::Dialogue => audio.play_in_channel(handle.clone(), &dialogue_channel),
+            Channel::Dialogue(track) => audio.play_in_channel(handle.clone(), &dialogue_channel(track)),
         }
     }
 
     for StopAudioEvent { channel } in ev_stop.iter() {
         match channel {
             Channel::Music => audio.stop_channel(&music_channel),
-            Channel::Dialogue => audio.stop_channel(&dialogue_channel),
+            Channel::Dialogue(track) => audio.stop_channel(&dialogue_channel(track)),
         }
     }
 }
 
+/// How loud every channel plays, from 0 to 1.
+pub struct Volume(pub f32);
+
+impl Default for Volume {
+    fn default() -> Volume {
+        Volume(1.)
+    }
+}
+
+/// Whether the channels were last paused, and the playback rate they were last given.
+struct ChannelState {
+    paused: bool,
+    rate: f32,
+}
+
+impl Default for ChannelState {
+    fn default() -> ChannelState {
+        ChannelState { paused: false, rate: 1. }
+    }
+}
+
+/// Pause the channels with the game clock, and play them faster or slower with its time scale
+/// so that voiced lines end when they are expected to.
+fn follow_clock(
+    clock: Res<GameClock>,
+    audio: Res<Audio>,
+    tracks: Query<&TrackId>,
+    added_tracks: Query<(), Added<TrackId>>,
+    music_channel: Local<MusicChannel>,
+    mut state: Local<ChannelState>,
+) {
+    let mut dialogue_channels: Vec<AudioChannel> = tracks.iter().map(dialogue_channel).collect();
+    dialogue_channels.push(dialogue_channel(&TrackId::main()));
+
+    if clock.is_paused() != state.paused {
+        state.paused = clock.is_paused();
+        for channel in dialogue_channels.iter().chain(Some(&music_channel.0)) {
+            match state.paused {
+                true => audio.pause_channel(channel),
+                false => audio.resume_channel(channel),
+            }
+        }
+    }
+
+    let rescaled = (clock.scale() - state.rate).abs() > f32::EPSILON;
+    if rescaled || (state.rate != 1. && added_tracks.iter().next().is_some()) {
+        state.rate = clock.scale();
+        for channel in dialogue_channels.iter().chain(Some(&music_channel.0)) {
+            audio.set_playback_rate_in_channel(state.rate, channel);
+        }
+    }
+}
+
+fn apply_volume(
+    volume: Res<Volume>,
+    audio: Res<Audio>,
+    tracks: Query<&TrackId>,
+    added_tracks: Query<(), Added<TrackId>>,
+    music_channel: Local<MusicChannel>,
+) {
+    // Tracks added by a new scene start out at full volume
+    if !volume.is_changed() && added_tracks.iter().next().is_none() {
+        return
+    }
+    audio.set_volume_in_channel(volume.0, &music_channel);
+    audio.set_volume_in_channel(volume.0, &dialogue_channel(&TrackId::main()));
+    for track in tracks.iter() {
+        audio.set_volume_in_channel(volume.0, &dialogue_channel(track));
+    }
+}
+
 pub struct AudioPlugin;
 
 impl Plugin for AudioPlugin {
@@ -81,6 +145,9 @@ impl Plugin for AudioPlugin {
         app
             .add_plugin(bevy_kira_audio::AudioPlugin)
             .add_system(events.system())
+            .add_system(follow_clock.system())
+            .init_resource::<Volume>()
+            .add_system(apply_volume.system())
             .add_event::<PlayAudioEvent>()
             .add_event::<StopAudioEvent>();
     }
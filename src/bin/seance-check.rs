@@ -0,0 +1,59 @@
+//! Validate scene scripts without opening a window.
+//!
+//! Usage: seance-check [SCENE...]
+//!        seance-check graph [SCENE]
+//!
+//! Scenes default to `assets/scenes/act1.scene.ron`. Asset paths are resolved against `assets/`.
+//! `graph` prints the scene's dialogue as Graphviz DOT instead, e.g. for `dot -Tsvg`.
+
+use seance::check::check_scene;
+use seance::graph::dot;
+use seance::scene::SceneDefinition;
+use std::path::Path;
+use std::process::exit;
+
+const DEFAULT_SCENE: &str = "assets/scenes/act1.scene.ron";
+
+fn read_scene(path: &str) -> SceneDefinition {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            exit(2);
+        }
+    };
+    match SceneDefinition::from_bytes(&bytes) {
+        Ok(scene) => scene,
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            exit(2);
+        }
+    }
+}
+
+fn main() {
+    let mut scenes: Vec<String> = std::env::args().skip(1).collect();
+    if scenes.first().map(String::as_str) == Some("graph") {
+        let path = scenes.get(1).map_or(DEFAULT_SCENE, String::as_str);
+        print!("{}", dot(&read_scene(path)));
+        return
+    }
+    if scenes.is_empty() {
+        scenes.push(DEFAULT_SCENE.to_string());
+    }
+
+    let mut total = 0;
+    for path in &scenes {
+        let scene = read_scene(path);
+        let problems = check_scene(&scene, Path::new("assets"));
+        for problem in &problems {
+            println!("{}: {}", path, problem);
+        }
+        total += problems.len();
+    }
+
+    if total > 0 {
+        println!("{} problem(s) found", total);
+        exit(1);
+    }
+}
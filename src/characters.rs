@@ -0,0 +1,48 @@
+use crate::registry::Registry;
+use bevy::prelude::{Color, Query};
+use serde::Deserialize;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(transparent)]
+pub struct CharacterId(pub String);
+
+pub struct Character {
+    pub name: String,
+    pub color: Color,
+    /// Played while one of the character's lines is spoken
+    pub talk_animation: Option<String>,
+}
+
+/// The names of everyone speaking a line, joined for display, e.g. "Jasmine and Melina".
+pub fn speaker_names(
+    speakers: &[CharacterId],
+    registry: &Registry,
+    characters: &Query<&Character>,
+) -> Option<String> {
+    let names: Vec<&str> = speakers
+        .iter()
+        .filter_map(|id| registry.character(id))
+        .filter_map(|entity| characters.get(entity).ok())
+        .map(|character| character.name.as_str())
+        .collect();
+
+    match names.split_last() {
+        None => None,
+        Some((last, [])) => Some(last.to_string()),
+        Some((last, rest)) => Some(format!("{} and {}", rest.join(", "), last)),
+    }
+}
+
+/// The talk animations of everyone speaking a line.
+pub fn talk_animations(
+    speakers: &[CharacterId],
+    registry: &Registry,
+    characters: &Query<&Character>,
+) -> Vec<String> {
+    speakers
+        .iter()
+        .filter_map(|id| registry.character(id))
+        .filter_map(|entity| characters.get(entity).ok())
+        .filter_map(|character| character.talk_animation.clone())
+        .collect()
+}
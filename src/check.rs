@@ -0,0 +1,467 @@
+use crate::characters::CharacterId;
+use crate::concepts::ConceptId;
+use crate::condition::Facts;
+use crate::dialogue::{LineId, TrackId};
+use crate::scene::{LineDefinition, SceneDefinition};
+use crate::story::Value;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+pub enum Problem {
+    DuplicateLine(LineId),
+    DuplicateConcept(ConceptId),
+    UnknownLine { line: LineId, target: LineId },
+    UnknownConcept { referrer: String, concept: ConceptId },
+    UnproducibleConcept(ConceptId),
+    Unreachable { line: LineId, reason: String },
+    Cycle(Vec<LineId>),
+    UnsetStoryValue { line: LineId, name: String },
+    UnknownCharacter { line: LineId, character: CharacterId },
+    UnknownTrack { line: LineId, track: TrackId },
+    EscalationOffTrack { line: LineId, target: LineId },
+    UnknownAnimation { referrer: String, animation: String },
+    MissingAsset { referrer: String, path: String },
+    NeverEnds,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::DuplicateLine(LineId(id)) => write!(f, "line {} is defined more than once", id),
+            Problem::DuplicateConcept(ConceptId(id)) => write!(f, "concept {} is defined more than once", id),
+            Problem::UnknownLine { line: LineId(line), target: LineId(target) } => {
+                write!(f, "line {} refers to unknown line {}", line, target)
+            }
+            Problem::UnknownConcept { referrer, concept: ConceptId(concept) } => {
+                write!(f, "{} refers to unknown concept {}", referrer, concept)
+            }
+            Problem::UnproducibleConcept(ConceptId(id)) => {
+                write!(f, "concept {} is not evoked by any object or recipe", id)
+            }
+            Problem::Unreachable { line: LineId(line), reason } => {
+                write!(f, "line {} can never be spoken: {}", line, reason)
+            }
+            Problem::Cycle(lines) => {
+                let ids: Vec<&str> = lines.iter().map(|LineId(id)| id.as_str()).collect();
+                write!(f, "lines require each other in a cycle: {}", ids.join(" -> "))
+            }
+            Problem::UnsetStoryValue { line: LineId(line), name } => {
+                write!(f, "line {} tests story value {} which no line sets", line, name)
+            }
+            Problem::UnknownCharacter { line: LineId(line), character: CharacterId(character) } => {
+                write!(f, "line {} is spoken by unknown character {}", line, character)
+            }
+            Problem::UnknownTrack { line: LineId(line), track: TrackId(track) } => {
+                write!(f, "line {} is spoken on track {} which has no text box", line, track)
+            }
+            Problem::EscalationOffTrack { line: LineId(line), target: LineId(target) } => {
+                write!(f, "line {} escalates to {} on another track", line, target)
+            }
+            Problem::UnknownAnimation { referrer, animation } => {
+                write!(f, "{} uses animation {} which no sprite defines", referrer, animation)
+            }
+            Problem::MissingAsset { referrer, path } => {
+                write!(f, "{} refers to missing asset {}", referrer, path)
+            }
+            Problem::NeverEnds => write!(f, "no line ends the act"),
+        }
+    }
+}
+
+/// Find everything in a scene that would silently do nothing when played.
+/// Asset paths are resolved against `assets`.
+pub fn check_scene(scene: &SceneDefinition, assets: &Path) -> Vec<Problem> {
+    let mut problems = vec![];
+
+    let mut concepts = HashSet::new();
+    for concept in &scene.concepts {
+        if !concepts.insert(&concept.id) {
+            problems.push(Problem::DuplicateConcept(concept.id.clone()));
+        }
+    }
+    let mut lines = HashMap::new();
+    for line in &scene.lines {
+        if lines.insert(&line.id, line).is_some() {
+            problems.push(Problem::DuplicateLine(line.id.clone()));
+        }
+    }
+    let groups: HashSet<&LineId> = scene.lines.iter().flat_map(|line| &line.groups).collect();
+
+    // Dangling references
+    let mut check_concept = |referrer: String, concept: &ConceptId| {
+        if !concepts.contains(concept) {
+            problems.push(Problem::UnknownConcept { referrer, concept: concept.clone() });
+        }
+    };
+    for concept in &scene.concepts {
+        for parent in concept.parents.iter().flatten() {
+            check_concept(format!("concept {}", concept.id.0), parent);
+        }
+    }
+    for sprite in &scene.sprites {
+        if let Some(concept) = &sprite.evokes {
+            check_concept(format!("sprite {}", sprite.name), concept);
+        }
+    }
+    for line in &scene.lines {
+        for concept in line.requires_concepts.iter()
+            .chain(&line.consumes_concepts)
+            .chain(&line.responds_to_concepts)
+            .chain(line.condition.iter().flat_map(|c| c.concepts()))
+        {
+            check_concept(format!("line {}", line.id.0), concept);
+        }
+    }
+    let story_values: HashSet<&String> = scene.lines
+        .iter()
+        .flat_map(|line| line.sets.keys().chain(line.increments.keys()))
+        .collect();
+    for line in &scene.lines {
+        for name in line.condition.iter().flat_map(|c| c.story_values()) {
+            if !story_values.contains(name) {
+                problems.push(Problem::UnsetStoryValue { line: line.id.clone(), name: name.clone() });
+            }
+        }
+    }
+    let characters: HashSet<&CharacterId> = scene.characters.iter().map(|c| &c.id).collect();
+    for line in &scene.lines {
+        for character in &line.speakers {
+            if !characters.contains(character) {
+                problems.push(Problem::UnknownCharacter { line: line.id.clone(), character: character.clone() });
+            }
+        }
+    }
+    let tracks: HashSet<&TrackId> = scene.tracks.iter().map(|track| &track.id).collect();
+    for line in &scene.lines {
+        if line.track != TrackId::main() && !tracks.contains(&line.track) {
+            problems.push(Problem::UnknownTrack { line: line.id.clone(), track: line.track.clone() });
+        }
+    }
+    for line in &scene.lines {
+        if let Some(escalated) = line.escalation.as_ref().and_then(|escalation| lines.get(&escalation.line)) {
+            if escalated.track != line.track {
+                problems.push(Problem::EscalationOffTrack { line: line.id.clone(), target: escalated.id.clone() });
+            }
+        }
+    }
+    if !scene.lines.iter().any(|line| line.ends_act) {
+        problems.push(Problem::NeverEnds);
+    }
+    for line in &scene.lines {
+        for target in line.requires_spoken.iter()
+            .chain(&line.conflicts_spoken)
+            .chain(line.escalation.iter().map(|escalation| &escalation.line))
+            .chain(line.condition.iter().flat_map(|c| c.lines()))
+        {
+            if !lines.contains_key(target) && !groups.contains(target) {
+                problems.push(Problem::UnknownLine { line: line.id.clone(), target: target.clone() });
+            }
+        }
+    }
+
+    // Concepts that can be evoked, either directly by an object or by joining others
+    let mut producible: HashSet<&ConceptId> = scene.sprites
+        .iter()
+        .filter_map(|sprite| sprite.evokes.as_ref())
+        .collect();
+    loop {
+        let joined: Vec<&ConceptId> = scene.concepts
+            .iter()
+            .filter(|concept| !producible.contains(&concept.id))
+            .filter(|concept| concept.parents
+                .iter()
+                .any(|parents| parents.iter().all(|p| producible.contains(p))))
+            .map(|concept| &concept.id)
+            .collect();
+        if joined.is_empty() {
+            break;
+        }
+        producible.extend(joined);
+    }
+    for concept in &scene.concepts {
+        if !producible.contains(&concept.id) {
+            problems.push(Problem::UnproducibleConcept(concept.id.clone()));
+        }
+    }
+
+    // Lines that some playthrough can speak, ignoring conflicts and repeat limits.
+    // Escalations are only spoken in place of the line they escalate.
+    let escalations: HashSet<&LineId> = scene.lines
+        .iter()
+        .filter_map(|line| line.escalation.as_ref())
+        .map(|escalation| &escalation.line)
+        .collect();
+    let mut spoken: HashSet<&LineId> = HashSet::new();
+    loop {
+        let newly_spoken: Vec<&LineDefinition> = scene.lines
+            .iter()
+            .filter(|line| !spoken.contains(&line.id) && !escalations.contains(&line.id))
+            .filter(|line| blocked_by(line, &spoken, &producible).is_none())
+            .collect();
+        if newly_spoken.is_empty() {
+            break;
+        }
+        for line in newly_spoken {
+            spoken.insert(&line.id);
+            spoken.extend(&line.groups);
+            if let Some(escalation) = &line.escalation {
+                if let Some(escalated) = lines.get(&escalation.line) {
+                    spoken.insert(&escalated.id);
+                    spoken.extend(&escalated.groups);
+                }
+            }
+        }
+    }
+    for line in scene.lines.iter().filter(|line| !escalations.contains(&line.id)) {
+        // Only report where a chain breaks, not every line downstream of it
+        if required_lines(line).any(|id| lines.contains_key(id) && !spoken.contains(id)) {
+            continue;
+        }
+        if let Some(reason) = blocked_by(line, &spoken, &producible) {
+            problems.push(Problem::Unreachable { line: line.id.clone(), reason });
+        }
+    }
+
+    problems.extend(find_cycles(&scene.lines).into_iter().map(Problem::Cycle));
+
+    // Animations and assets
+    let animations: HashSet<&String> = scene.sprites
+        .iter()
+        .filter_map(|sprite| sprite.animations.as_ref())
+        .flat_map(|animations| animations.named.keys())
+        .collect();
+    let mut check_animation = |referrer: String, animation: &String| {
+        if !animations.contains(animation) {
+            problems.push(Problem::UnknownAnimation { referrer, animation: animation.clone() });
+        }
+    };
+    for character in &scene.characters {
+        if let Some(animation) = &character.talk_animation {
+            check_animation(format!("character {}", character.id.0), animation);
+        }
+    }
+    for line in &scene.lines {
+        for animation in line.animations.iter()
+            .chain(&line.starts_animations)
+            .chain(&line.ends_animations)
+        {
+            check_animation(format!("line {}", line.id.0), animation);
+        }
+    }
+
+    let mut check_asset = |referrer: String, path: &str| {
+        if !assets.join(path).is_file() {
+            problems.push(Problem::MissingAsset { referrer, path: path.to_string() });
+        }
+    };
+    for sprite in &scene.sprites {
+        check_asset(format!("sprite {}", sprite.name), &sprite.texture);
+    }
+    for line in &scene.lines {
+        for path in line.audio.iter()
+            .chain(&line.music)
+            .chain(line.variants.iter().filter_map(|variant| variant.audio.as_ref()))
+        {
+            check_asset(format!("line {}", line.id.0), path);
+        }
+    }
+
+    problems
+}
+
+/// The first condition that stops `line` being spoken, given what has been spoken and evoked.
+fn blocked_by(
+    line: &LineDefinition,
+    spoken: &HashSet<&LineId>,
+    producible: &HashSet<&ConceptId>,
+) -> Option<String> {
+    if let Some(LineId(id)) = line.requires_spoken.iter().find(|id| !spoken.contains(id)) {
+        return Some(format!("requires {} which is never spoken", id));
+    }
+    if let Some(ConceptId(id)) = line.requires_concepts.iter()
+        .chain(&line.responds_to_concepts)
+        .find(|id| !producible.contains(id))
+    {
+        return Some(format!("requires concept {} which is never evoked", id));
+    }
+    if line.requires_any_concept && producible.is_empty() {
+        return Some("requires a concept but none can be evoked".to_string());
+    }
+    if let Some(condition) = &line.condition {
+        if !condition.can_hold(&Reachable { spoken, producible }) {
+            return Some("its condition can never hold".to_string());
+        }
+    }
+    None
+}
+
+/// Everything some playthrough can have spoken or evoked.
+struct Reachable<'a, 'b> {
+    spoken: &'a HashSet<&'b LineId>,
+    producible: &'a HashSet<&'b ConceptId>,
+}
+
+impl<'a, 'b> Facts for Reachable<'a, 'b> {
+    fn spoken(&self, line: &LineId) -> bool {
+        self.spoken.contains(line)
+    }
+
+    fn evoked(&self, concept: &ConceptId) -> bool {
+        self.producible.contains(concept)
+    }
+
+    fn any_evoked(&self) -> bool {
+        !self.producible.is_empty()
+    }
+
+    fn story_value(&self, _name: &str) -> Option<&Value> {
+        None
+    }
+}
+
+/// Lines that must be spoken before `line` can be.
+fn required_lines(line: &LineDefinition) -> impl Iterator<Item = &LineId> {
+    line.requires_spoken
+        .iter()
+        .chain(line.condition.iter().flat_map(|c| c.required_lines()))
+}
+
+/// Cycles in the graph of lines required by others, each listed from its first line back to itself.
+fn find_cycles(lines: &[LineDefinition]) -> Vec<Vec<LineId>> {
+    let requires: HashMap<&LineId, Vec<&LineId>> = lines
+        .iter()
+        .map(|line| (&line.id, required_lines(line).collect()))
+        .collect();
+
+    let mut cycles = vec![];
+    let mut done: HashSet<&LineId> = HashSet::new();
+    for line in lines {
+        let mut path: Vec<&LineId> = vec![];
+        visit(&line.id, &requires, &mut path, &mut done, &mut cycles);
+    }
+    cycles
+}
+
+fn visit<'a>(
+    id: &'a LineId,
+    requires: &HashMap<&'a LineId, Vec<&'a LineId>>,
+    path: &mut Vec<&'a LineId>,
+    done: &mut HashSet<&'a LineId>,
+    cycles: &mut Vec<Vec<LineId>>,
+) {
+    if done.contains(id) {
+        return;
+    }
+    if let Some(start) = path.iter().position(|p| *p == id) {
+        let mut cycle: Vec<LineId> = path[start..].iter().map(|p| (*p).clone()).collect();
+        cycle.push(id.clone());
+        cycles.push(cycle);
+        return;
+    }
+
+    path.push(id);
+    if let Some(targets) = requires.get(id) {
+        for target in targets.iter() {
+            visit(target, requires, path, done, cycles);
+        }
+    }
+    path.pop();
+    done.insert(id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(ron: &str) -> Vec<Problem> {
+        let ron = format!("#![enable(implicit_some)]\n{}", ron);
+        let scene = SceneDefinition::from_bytes(ron.as_bytes()).unwrap();
+        check_scene(&scene, Path::new("assets"))
+    }
+
+    #[test]
+    fn unknown_required_line() {
+        let problems = check(r#"(
+            lines: [(id: "hello", text: "Hello.", requires_spoken: ["goodbye"], ends_act: true)],
+        )"#);
+        assert!(problems.iter().any(|problem| matches!(problem,
+            Problem::UnknownLine { line, target } if line.0 == "hello" && target.0 == "goodbye")));
+    }
+
+    #[test]
+    fn escalation_off_track() {
+        let problems = check(r#"(
+            tracks: [(id: "whispers", text_box: (top: 15))],
+            lines: [
+                (id: "wrong", text: "Wrong.", repeatable: true, escalation: (after: 2, line: "hint")),
+                (id: "hint", text: "Hint.", track: "whispers", ends_act: true),
+            ],
+        )"#);
+        assert!(problems.iter().any(|problem| matches!(problem,
+            Problem::EscalationOffTrack { line, target } if line.0 == "wrong" && target.0 == "hint")));
+    }
+
+    #[test]
+    fn condition_cycle() {
+        let problems = check(r#"(
+            lines: [
+                (id: "a", text: "A.", condition: All([Spoken("b")])),
+                (id: "b", text: "B.", requires_spoken: ["a"], ends_act: true),
+            ],
+        )"#);
+        assert!(problems.iter().any(|problem| matches!(problem, Problem::Cycle(lines) if lines.len() == 3)));
+    }
+
+    #[test]
+    fn unproducible_concept() {
+        let problems = check(r#"(
+            concepts: [(id: "key", description: "A key"), (id: "door", description: "A door", parents: [["key"]])],
+            lines: [(id: "hello", text: "Hello.", requires_concepts: ["door"], ends_act: true)],
+        )"#);
+        let unproducible: Vec<&str> = problems
+            .iter()
+            .filter_map(|problem| match problem {
+                Problem::UnproducibleConcept(ConceptId(id)) => Some(id.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(unproducible, vec!["key", "door"]);
+        assert!(problems.iter().any(|problem| matches!(problem, Problem::Unreachable { line, .. } if line.0 == "hello")));
+    }
+
+    #[test]
+    fn unknown_animation() {
+        let problems = check(r#"(
+            sprites: [(
+                name: "mother",
+                texture: "background.png",
+                atlas: Grid(tile_size: (1., 1.), columns: 1, rows: 1),
+                animations: (default: (kind: Blink, frames: [0]), named: {"mother_talk": (kind: Talk, frames: [0])}),
+            )],
+            lines: [(id: "hello", text: "Hello.", animations: ["mother_tlak"], ends_act: true)],
+        )"#);
+        assert!(problems.iter().any(|problem| matches!(problem,
+            Problem::UnknownAnimation { animation, .. } if animation == "mother_tlak")));
+        assert!(!problems.iter().any(|problem| matches!(problem,
+            Problem::UnknownAnimation { animation, .. } if animation == "mother_talk")));
+    }
+
+    #[test]
+    fn missing_asset() {
+        let problems = check(r#"(
+            lines: [(id: "hello", text: "Hello.", audio: "dialogue/missing.mp3", ends_act: true)],
+        )"#);
+        assert!(problems.iter().any(|problem| matches!(problem,
+            Problem::MissingAsset { path, .. } if path == "dialogue/missing.mp3")));
+    }
+
+    #[test]
+    fn never_ends() {
+        let problems = check(r#"(lines: [(id: "hello", text: "Hello.")])"#);
+        assert!(problems.iter().any(|problem| matches!(problem, Problem::NeverEnds)));
+
+        let problems = check(r#"(lines: [(id: "hello", text: "Hello.", ends_act: true)])"#);
+        assert!(problems.is_empty());
+    }
+}
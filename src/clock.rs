@@ -0,0 +1,87 @@
+use bevy::prelude::{AppBuilder, CoreStage, IntoSystem, Plugin, Res, ResMut, Time};
+use std::time::Duration;
+
+/// The time that has passed in the game, as seen by dialogue, concepts, animation and the ghost.
+///
+/// Advanced from `Time` while the game runs, standing still while it is paused and running
+/// faster or slower by its scale, or stepped directly when simulating.
+pub struct GameClock {
+    elapsed: Duration,
+    delta: Duration,
+    paused: bool,
+    scale: f32,
+}
+
+impl Default for GameClock {
+    fn default() -> GameClock {
+        GameClock {
+            elapsed: Duration::default(),
+            delta: Duration::default(),
+            paused: false,
+            scale: 1.,
+        }
+    }
+}
+
+impl GameClock {
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// How many seconds of game time pass for each real second.
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale.max(0.);
+    }
+
+    /// Carry on from `elapsed`, as when loading a saved game.
+    pub fn restore(&mut self, elapsed: Duration) {
+        self.delta = Duration::default();
+        self.elapsed = elapsed;
+    }
+
+    pub fn advance(&mut self, delta: Duration) {
+        self.delta = delta;
+        self.elapsed += delta;
+    }
+}
+
+fn tick(time: Res<Time>, mut clock: ResMut<GameClock>) {
+    let delta = match clock.paused {
+        true => Duration::default(),
+        false => time.delta().mul_f32(clock.scale),
+    };
+    clock.advance(delta);
+}
+
+pub struct ClockPlugin;
+
+impl Plugin for ClockPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<GameClock>()
+            .add_system_to_stage(CoreStage::First, tick.system());
+    }
+}
@@ -1,42 +1,58 @@
 use bevy::prelude::{
-    Entity, Res, Query, With, Without, Plugin, IntoSystem, Commands, Time,
-    Added, AppBuilder, info, EventReader,
+    Entity, Res, Query, With, Without, Plugin, IntoSystem, Commands,
+    Added, AppBuilder, info, warn, EventReader,
 };
+use crate::clock::GameClock;
 use crate::ghost::GhostInteractionEvent;
-use std::collections::HashSet;
+use crate::registry::Registry;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct ConceptId(pub String);
+
 pub struct Concept {
     pub description: String,
-    pub parents: Vec<HashSet<Entity>>,
+    pub parents: Vec<HashSet<ConceptId>>,
 }
 
 pub struct Evoked(pub Duration);
 
-pub struct EvokesConcept(pub Entity);
+pub struct EvokesConcept(pub ConceptId);
 pub struct ClearsConcepts;
 
+#[allow(clippy::too_many_arguments)]
 fn evoke_concepts(
     evoke_query: Query<&EvokesConcept>,
     clear_query: Query<&ClearsConcepts>,
     concept_query: Query<&Concept>,
     evoked_concept_query: Query<(Entity, &Concept), With<Evoked>>,
+    registry: Res<Registry>,
     mut commands: Commands,
-    time: Res<Time>,
+    clock: Res<GameClock>,
     mut ev_interaction: EventReader<GhostInteractionEvent>,
 ) {
     for GhostInteractionEvent { ghost: _, target } in ev_interaction.iter() {
-        if let Ok(&EvokesConcept(concept)) = evoke_query.get(*target) {
+        if let Ok(EvokesConcept(id)) = evoke_query.get(*target) {
+            let concept = match registry.concept(id) {
+                Some(concept) => concept,
+                None => {
+                    warn!("Item evokes unknown concept {:?}", id);
+                    break
+                }
+            };
             commands
                 .entity(concept)
-                .insert(Evoked(time.time_since_startup()));
+                .insert(Evoked(clock.elapsed()));
             if let Ok(c) = concept_query.get(concept) {
                 info!("Activated item to evoke {}", c.description);
             }
             break
         }
 
-        if let Ok(_) = clear_query.get(*target) {
+        if clear_query.get(*target).is_ok() {
             for (e, c) in evoked_concept_query.iter() {
                 commands
                     .entity(e)
@@ -50,18 +66,20 @@ fn evoke_concepts(
 
 fn join_concepts(
     new_query: Query<(), (With<Concept>, Added<Evoked>)>,
-    evoked_query: Query<(Entity, &Evoked), With<Concept>>,
+    evoked_query: Query<(&ConceptId, &Evoked), With<Concept>>,
     concept_query: Query<(Entity, &Concept), Without<Evoked>>,
+    registry: Res<Registry>,
     mut commands: Commands,
-    time: Res<Time>,
+    clock: Res<GameClock>,
 ) {
-    if let None = new_query.iter().next() {
+    if new_query.iter().next().is_none() {
         return
     }
 
-    let evoked_set: HashSet<Entity> = evoked_query.iter()
-        .map(|(e, _)| e)
+    let evoked_times: HashMap<ConceptId, Duration> = evoked_query.iter()
+        .map(|(id, Evoked(timestamp))| (id.clone(), *timestamp))
         .collect();
+    let evoked_set: HashSet<ConceptId> = evoked_times.keys().cloned().collect();
 
     let to_join = concept_query.iter()
         .flat_map(
@@ -75,20 +93,20 @@ fn join_concepts(
         )
         .max_by_key(
             |(_, parents)| parents.iter().map(
-                |e| evoked_query.get(*e).unwrap().1.0
+                |id| evoked_times[id]
             ).collect::<Vec<Duration>>()
         );
 
     if let Some((entity, parents)) = to_join {
         commands
             .entity(entity)
-            .insert(Evoked(time.time_since_startup()));
+            .insert(Evoked(clock.elapsed()));
         if let Ok((_, c)) = concept_query.get(entity) {
             info!("Joined concepts to evoke {}", c.description);
         }
 
-        for parent in parents.iter() {
-            commands.entity(*parent)
+        for parent in parents.iter().filter_map(|id| registry.concept(id)) {
+            commands.entity(parent)
                 .remove::<Evoked>();
         }
     }
@@ -0,0 +1,107 @@
+use crate::concepts::ConceptId;
+use crate::dialogue::LineId;
+use crate::story::Value;
+use serde::Deserialize;
+
+/// A test on the state of the dialogue, combining the same predicates as a line's
+/// `requires_*` and `conflicts_*` fields, and tests of the story state, with `All`, `Any`
+/// and `Not`.
+#[derive(Clone, Debug, Deserialize)]
+pub enum Condition {
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+    Not(Box<Condition>),
+    Spoken(LineId),
+    Evoked(ConceptId),
+    AnyEvoked,
+    /// A story flag is set to true
+    Flag(String),
+    /// A story value is set to exactly this
+    Equals(String, Value),
+    /// A story counter has reached at least this
+    AtLeast(String, i64),
+}
+
+/// What conditions are tested against.
+pub trait Facts {
+    fn spoken(&self, line: &LineId) -> bool;
+    fn evoked(&self, concept: &ConceptId) -> bool;
+    fn any_evoked(&self) -> bool;
+    fn story_value(&self, name: &str) -> Option<&Value>;
+}
+
+impl Condition {
+    pub fn holds(&self, facts: &impl Facts) -> bool {
+        match self {
+            Condition::All(conditions) => conditions.iter().all(|c| c.holds(facts)),
+            Condition::Any(conditions) => conditions.iter().any(|c| c.holds(facts)),
+            Condition::Not(condition) => !condition.holds(facts),
+            Condition::Spoken(line) => facts.spoken(line),
+            Condition::Evoked(concept) => facts.evoked(concept),
+            Condition::AnyEvoked => facts.any_evoked(),
+            Condition::Flag(name) => facts.story_value(name) == Some(&Value::Bool(true)),
+            Condition::Equals(name, value) => facts.story_value(name) == Some(value),
+            Condition::AtLeast(name, minimum) => match facts.story_value(name) {
+                Some(Value::Int(value)) => value >= minimum,
+                _ => false,
+            },
+        }
+    }
+
+    /// Whether the condition could hold once everything in `facts` has happened,
+    /// assuming anything negated can still be avoided and the story can reach any state.
+    pub fn can_hold(&self, facts: &impl Facts) -> bool {
+        match self {
+            Condition::All(conditions) => conditions.iter().all(|c| c.can_hold(facts)),
+            Condition::Any(conditions) => conditions.iter().any(|c| c.can_hold(facts)),
+            Condition::Not(_) => true,
+            Condition::Flag(_) | Condition::Equals(..) | Condition::AtLeast(..) => true,
+            condition => condition.holds(facts),
+        }
+    }
+
+    /// Every line the condition mentions, wherever it is.
+    pub fn lines(&self) -> Vec<&LineId> {
+        match self {
+            Condition::All(conditions) | Condition::Any(conditions) => {
+                conditions.iter().flat_map(|c| c.lines()).collect()
+            }
+            Condition::Not(condition) => condition.lines(),
+            Condition::Spoken(line) => vec![line],
+            _ => vec![],
+        }
+    }
+
+    /// Every concept the condition mentions, wherever it is.
+    pub fn concepts(&self) -> Vec<&ConceptId> {
+        match self {
+            Condition::All(conditions) | Condition::Any(conditions) => {
+                conditions.iter().flat_map(|c| c.concepts()).collect()
+            }
+            Condition::Not(condition) => condition.concepts(),
+            Condition::Evoked(concept) => vec![concept],
+            _ => vec![],
+        }
+    }
+
+    /// Every story value the condition tests.
+    pub fn story_values(&self) -> Vec<&String> {
+        match self {
+            Condition::All(conditions) | Condition::Any(conditions) => {
+                conditions.iter().flat_map(|c| c.story_values()).collect()
+            }
+            Condition::Not(condition) => condition.story_values(),
+            Condition::Flag(name) | Condition::Equals(name, _) | Condition::AtLeast(name, _) => vec![name],
+            _ => vec![],
+        }
+    }
+
+    /// Lines that must have been spoken for the condition to hold.
+    pub fn required_lines(&self) -> Vec<&LineId> {
+        match self {
+            Condition::All(conditions) => conditions.iter().flat_map(|c| c.required_lines()).collect(),
+            Condition::Spoken(line) => vec![line],
+            _ => vec![],
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use crate::clock::GameClock;
+use crate::concepts::{ConceptId, Evoked};
+use crate::dialogue::{blocked, escalation, DialogueFacts, Interrupted, Line, LineId, Speaking, Spoken, TrackId};
+use crate::idle::IdleTimer;
+use crate::registry::Registry;
+use crate::story::StoryState;
+use bevy::prelude::{
+    AlignSelf, AppBuilder, AssetServer, Assets, BuildChildren, Color, ColorMaterial, Commands,
+    DespawnRecursiveExt, Entity, HorizontalAlign, Input, IntoSystem, KeyCode, NodeBundle, Plugin,
+    PositionType, Query, Rect, Res, ResMut, Size, Style, Text, TextAlignment, TextBundle,
+    TextSection, TextStyle, Val, VerticalAlign, With,
+};
+
+struct DebuggerPanel;
+
+struct DebuggerText;
+
+/// F3 opens and closes the debugger.
+fn toggle_debugger(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    panel_query: Query<Entity, With<DebuggerPanel>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F3) {
+        return
+    }
+
+    if let Some(panel) = panel_query.iter().next() {
+        commands
+            .entity(panel)
+            .despawn_recursive();
+        return
+    }
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(20.0),
+                    right: Val::Px(20.0),
+                    ..Default::default()
+                },
+                size: Size {
+                    width: Val::Px(620.),
+                    height: Val::Px(720. - 2. * 20.),
+                },
+                ..Default::default()
+            },
+            material: materials.add(Color::rgba(0., 0., 0., 0.85).into()),
+            ..Default::default()
+        })
+        .insert(DebuggerPanel)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    style: Style {
+                        align_self: AlignSelf::FlexEnd,
+                        margin: Rect::all(Val::Px(10.0)),
+                        max_size: Size {
+                            width: Val::Px(600.),
+                            height: Val::Undefined,
+                        },
+                        ..Default::default()
+                    },
+                    text: Text {
+                        sections: vec![],
+                        alignment: TextAlignment {
+                            vertical: VerticalAlign::Top,
+                            horizontal: HorizontalAlign::Left,
+                        },
+                    },
+                    ..Default::default()
+                })
+                .insert(DebuggerText);
+        });
+}
+
+/// Where a line stands, most pressing first.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Standing {
+    Speaking,
+    Interrupted,
+    Ready,
+    Blocked,
+}
+
+impl Standing {
+    fn color(&self) -> Color {
+        match self {
+            Standing::Speaking => Color::GOLD,
+            Standing::Interrupted => Color::ORANGE,
+            Standing::Ready => Color::GREEN,
+            Standing::Blocked => Color::GRAY,
+        }
+    }
+}
+
+/// Every line and why it can or cannot be spoken, then the concepts evoked so far.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn render_debugger(
+    panel_query: Query<(), With<DebuggerPanel>>,
+    mut text_query: Query<&mut Text, With<DebuggerText>>,
+    lines: Query<(Entity, &LineId, &Line, Option<&Speaking>, Option<&Interrupted>)>,
+    spoken_query: Query<&Spoken>,
+    concept_query: Query<Entity, With<Evoked>>,
+    evoked_query: Query<(&ConceptId, &Evoked)>,
+    registry: Res<Registry>,
+    story: Res<StoryState>,
+    clock: Res<GameClock>,
+    idle: Res<IdleTimer>,
+    asset_server: Res<AssetServer>,
+) {
+    let mut text = match (panel_query.iter().next(), text_query.iter_mut().next()) {
+        (Some(()), Some(text)) => text,
+        _ => return,
+    };
+
+    let facts = DialogueFacts {
+        registry: &registry,
+        spoken_query: &spoken_query,
+        concept_query: &concept_query,
+        story: &story,
+    };
+    let now = clock.elapsed();
+    let escalations: Vec<&LineId> = lines
+        .iter()
+        .filter_map(|(_, _, line, ..)| line.escalation.as_ref())
+        .map(|escalation| &escalation.line)
+        .collect();
+
+    let mut rows: Vec<(Standing, i32, String)> = lines
+        .iter()
+        .map(|(entity, id, line, speaking, interrupted)| {
+            let (standing, status) = if speaking.is_some() {
+                (Standing::Speaking, "speaking".to_string())
+            } else if interrupted.is_some() {
+                (Standing::Interrupted, "interrupted".to_string())
+            } else if escalations.contains(&id) {
+                (Standing::Blocked, "only spoken in place of another line".to_string())
+            } else {
+                let available = |e: Entity| match lines.get(e) {
+                    Ok((_, _, line, None, None)) => Some(line),
+                    _ => None,
+                };
+                let idle = idle.idle(now);
+                let reason = blocked(entity, line, &facts, now, idle)
+                    .or_else(|| escalation(entity, line, available, &facts, now, idle).err());
+                match reason {
+                    Some(reason) => (Standing::Blocked, reason.to_string()),
+                    None => (Standing::Ready, "ready".to_string()),
+                }
+            };
+            let track = match line.track == TrackId::main() {
+                true => String::new(),
+                false => format!(" [{}]", line.track.0),
+            };
+            (standing, line.priority, format!("{:>4}  {}{}  {}\n", line.priority, id.0, track, status))
+        })
+        .collect();
+    rows.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)).then(a.2.cmp(&b.2)));
+
+    let mut evoked: Vec<(&ConceptId, &Evoked)> = evoked_query.iter().collect();
+    evoked.sort_by_key(|(_, Evoked(time))| *time);
+
+    let style = |color: Color| TextStyle {
+        font: asset_server.load("GloriaHallelujah-Regular.ttf"),
+        font_size: 16.0,
+        color,
+    };
+    text.sections = vec![TextSection { value: "Lines\n".to_string(), style: style(Color::WHITE) }];
+    text.sections.extend(rows
+        .into_iter()
+        .map(|(standing, _, row)| TextSection { value: row, style: style(standing.color()) }));
+    text.sections.push(TextSection { value: "\nEvoked concepts\n".to_string(), style: style(Color::WHITE) });
+    text.sections.extend(evoked
+        .into_iter()
+        .map(|(ConceptId(id), Evoked(time))| TextSection {
+            value: format!("{:>7.1}s  {}\n", time.as_secs_f32(), id),
+            style: style(Color::WHITE),
+        }));
+}
+
+/// A panel listing every line with why it is or isn't ready to be spoken, for script authors.
+pub struct DebuggerPlugin;
+
+impl Plugin for DebuggerPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system(toggle_debugger.system())
+            .add_system(render_debugger.system());
+    }
+}
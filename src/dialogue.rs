@@ -1,17 +1,53 @@
 use bevy::prelude::{
-    Commands, Plugin, AppBuilder, IntoSystem, TextBundle, Style, AlignSelf,
-    PositionType, Rect, Val, Text, TextStyle, Color, TextAlignment, info,
-    HorizontalAlign, VerticalAlign, Res, AssetServer, Time, Query, With, UiCameraBundle,
-    Entity, EventWriter, Handle, Size, AlignContent, AlignItems,
+    Commands, Plugin, AppBuilder, IntoSystem, info, Res, Query, With, Without, Entity,
+    EventReader, EventWriter, Handle, ResMut, CoreStage,
+    ParallelSystemDescriptorCoercion,
 };
+use bevy::asset::create_platform_default_asset_io;
+use bevy::ecs::system::SystemParam;
 use bevy_kira_audio::AudioSource;
-use crate::concepts::Evoked;
+use crate::characters::{talk_animations, Character, CharacterId};
+use crate::clock::GameClock;
+use crate::concepts::{ConceptId, Evoked};
+use crate::condition::{Condition, Facts};
+use crate::duration::{apply_measured_audio, measure_audio, AudioIo, AutoDuration, AutoDurationSettings};
+use crate::idle::{track_activity, IdleTimer};
+use crate::registry::Registry;
 use crate::question_display::{SetQuestionEvent, ClearQuestionEvent};
 use crate::audio::{PlayAudioEvent, StopAudioEvent, Channel};
+use crate::story::{StoryState, Value};
+use crate::variants::{choose_variants, DialogueRng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
 use std::time::Duration;
 
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct LineId(pub String);
+
+/// A sequence of lines spoken one at a time, alongside any other tracks.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(transparent)]
+pub struct TrackId(pub String);
+
+impl TrackId {
+    /// The track of the main conversation, which the player can skip through.
+    pub fn main() -> TrackId {
+        TrackId("main".to_string())
+    }
+}
+
+impl Default for TrackId {
+    fn default() -> TrackId {
+        TrackId::main()
+    }
+}
+
 pub struct Line {
     pub text: String,
+    pub speakers: Vec<CharacterId>,
+    pub track: TrackId,
     pub priority: i32,
     pub duration: Duration,
     pub audio: Option<Handle<AudioSource>>,
@@ -19,25 +55,45 @@ pub struct Line {
     pub stop_audio: bool,
     pub stop_music: bool,
     pub repeatable: bool,
-    pub responds_to_concepts: Vec<Entity>,
-    pub groups: Vec<Entity>,
+    /// The most times a repeatable line can be spoken in all
+    pub max_repeats: Option<usize>,
+    /// How long after a repeatable line starts before it can start again
+    pub cooldown: Duration,
+    pub escalation: Option<Escalation>,
+    /// How long nothing must have happened before the line can be spoken, for hints
+    pub idle_after: Option<Duration>,
+    /// Whether another line can cut in while this one is spoken
+    pub interruptible: bool,
+    /// The lowest priority of line that can cut in, which must also be higher than the line's own
+    pub interrupt_priority: i32,
+    /// What happens to the line after it is cut off
+    pub resume: Resume,
+    pub responds_to_concepts: Vec<ConceptId>,
+    pub groups: Vec<LineId>,
     pub animations: Vec<String>,
     pub question: Option<String>,
     pub clear_question: bool,
     pub starts_animations: Vec<String>,
     pub ends_animations: Vec<String>,
-    pub requires_concepts: Vec<Entity>,
-    pub consumes_concepts: Vec<Entity>,
+    /// Whether the act is over once this line finishes
+    pub ends_act: bool,
+    pub requires_concepts: Vec<ConceptId>,
+    pub consumes_concepts: Vec<ConceptId>,
     pub requires_any_concept: bool,
     pub consumes_all_concepts: bool,
-    pub requires_spoken: Vec<Entity>,
-    pub conflicts_spoken: Vec<Entity>,
+    pub requires_spoken: Vec<LineId>,
+    pub conflicts_spoken: Vec<LineId>,
+    pub condition: Option<Condition>,
+    pub sets: HashMap<String, Value>,
+    pub increments: HashMap<String, i64>,
 }
 
 impl Default for Line {
     fn default() -> Line {
         Line {
             text: "".to_string(),
+            speakers: vec![],
+            track: TrackId::main(),
             priority: 0,
             duration: Duration::from_secs(3),
             audio: None,
@@ -45,6 +101,13 @@ impl Default for Line {
             stop_audio: false,
             stop_music: false,
             repeatable: false,
+            max_repeats: None,
+            cooldown: Duration::default(),
+            escalation: None,
+            idle_after: None,
+            interruptible: false,
+            interrupt_priority: 0,
+            resume: Resume::Never,
             responds_to_concepts: vec![],
             groups: vec![],
             animations: vec![],
@@ -52,255 +115,583 @@ impl Default for Line {
             clear_question: false,
             starts_animations: vec![],
             ends_animations: vec![],
+            ends_act: false,
             requires_concepts: vec![],
             consumes_concepts: vec![],
             requires_any_concept: false,
             consumes_all_concepts: false,
             requires_spoken: vec![],
             conflicts_spoken: vec![],
+            condition: None,
+            sets: HashMap::new(),
+            increments: HashMap::new(),
         }
     }
 }
 
-pub struct Spoken(Vec<Duration>);
+/// Once a line has been spoken `after` times, `line` is spoken in its place, which must be on the
+/// same track,
+/// e.g. a hint after too many wrong guesses. From then on the line can only be spoken while `line`
+/// could be, so a hint given as often as it allows leaves nothing to say.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Escalation {
+    pub after: usize,
+    pub line: LineId,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum Resume {
+    Never,
+    /// Start the line again from the beginning
+    Restart,
+    /// Pick the line up where it was cut off. Voice audio cannot be resumed part way through,
+    /// so voiced lines restart instead.
+    Continue,
+}
+
+/// A line cut off by another, waiting to be resumed.
+pub struct Interrupted {
+    pub at: Duration,
+    /// How long the line had been spoken for
+    pub spoken_for: Duration,
+}
+
+pub struct Spoken(pub Vec<Duration>);
 
 pub struct Speaking;
 
 pub struct AnimationStartEvent(pub String);
 pub struct AnimationEndEvent(pub String);
 
+/// Ends the line currently being spoken on the main track early.
+pub struct AdvanceEvent;
+
+/// A line has started being spoken, or resumed after being interrupted.
+pub struct LineStartedEvent {
+    pub line: Entity,
+    pub id: LineId,
+}
+
+/// A line has stopped being spoken. It was skipped if it was cut short, whether by the player
+/// or by another line interrupting it.
+pub struct LineFinishedEvent {
+    pub line: Entity,
+    pub id: LineId,
+    pub skipped: bool,
+}
+
+/// A line that ends the act has finished.
+pub struct ActFinishedEvent;
+
+/// The state of the dialogue that line conditions are tested against.
+pub(crate) struct DialogueFacts<'a, 'w, 's> {
+    pub registry: &'a Registry,
+    pub spoken_query: &'a Query<'w, &'s Spoken>,
+    pub concept_query: &'a Query<'w, Entity, With<Evoked>>,
+    pub story: &'a StoryState,
+}
+
+impl<'a, 'w, 's> DialogueFacts<'a, 'w, 's> {
+    fn spoken_entity(&self, entity: Option<Entity>) -> Option<&Spoken> {
+        entity.and_then(|e| self.spoken_query.get(e).ok())
+    }
+}
+
+impl<'a, 'w, 's> Facts for DialogueFacts<'a, 'w, 's> {
+    fn spoken(&self, line: &LineId) -> bool {
+        self.registry
+            .line(line)
+            .and_then(|e| self.spoken_query.get(e).ok())
+            .is_some()
+    }
+
+    fn evoked(&self, concept: &ConceptId) -> bool {
+        self.registry
+            .concept(concept)
+            .and_then(|e| self.concept_query.get(e).ok())
+            .is_some()
+    }
+
+    fn any_evoked(&self) -> bool {
+        self.concept_query.iter().next().is_some()
+    }
+
+    fn story_value(&self, name: &str) -> Option<&Value> {
+        self.story.get(name)
+    }
+}
+
+/// Why a line cannot be spoken yet, as far as the line itself is concerned.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Blocked {
+    MissingConcept(ConceptId),
+    NoConceptEvoked,
+    OutsideResponseWindow(ConceptId),
+    NotRepeatable,
+    RepeatedEnough(usize),
+    /// With how long there is left to wait
+    CoolingDown(Duration),
+    NotYetSpoken(LineId),
+    ConflictSpoken(LineId),
+    /// With how much longer the player must be idle
+    NotIdle(Duration),
+    ConditionFails,
+    /// The line it escalates to cannot be spoken, and why
+    EscalationBlocked(LineId, Box<Blocked>),
+    /// The line it escalates to is unknown, on another track, still being timed, or part way
+    /// through being spoken
+    EscalationUnavailable(LineId),
+}
+
+impl fmt::Display for Blocked {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Blocked::MissingConcept(ConceptId(concept)) => write!(f, "needs concept {}", concept),
+            Blocked::NoConceptEvoked => write!(f, "needs any concept"),
+            Blocked::OutsideResponseWindow(ConceptId(concept)) => {
+                write!(f, "outside the response window for {}", concept)
+            }
+            Blocked::NotRepeatable => write!(f, "already spoken"),
+            Blocked::RepeatedEnough(times) => write!(f, "already spoken {} times", times),
+            Blocked::CoolingDown(left) => write!(f, "cooling down for {:.1}s", left.as_secs_f32()),
+            Blocked::NotYetSpoken(LineId(line)) => write!(f, "needs {} spoken", line),
+            Blocked::ConflictSpoken(LineId(line)) => write!(f, "conflicts with {}", line),
+            Blocked::NotIdle(left) => write!(f, "waiting for {:.1}s more idleness", left.as_secs_f32()),
+            Blocked::ConditionFails => write!(f, "condition does not hold"),
+            Blocked::EscalationBlocked(LineId(line), reason) => write!(f, "escalates to {}, which is {}", line, reason),
+            Blocked::EscalationUnavailable(LineId(line)) => write!(f, "escalates to {}, which is unavailable", line),
+        }
+    }
+}
+
+/// Test a line against the concepts, lines and story so far, ignoring whether its track is free.
+pub(crate) fn blocked(
+    entity: Entity,
+    line: &Line,
+    facts: &DialogueFacts,
+    now: Duration,
+    idle: Duration,
+) -> Option<Blocked> {
+    // Check concept requirements
+    for concept in line.requires_concepts.iter().chain(&line.responds_to_concepts) {
+        if !facts.evoked(concept) {
+            return Some(Blocked::MissingConcept(concept.clone()));
+        }
+    }
+
+    if line.requires_any_concept && !facts.any_evoked() {
+        return Some(Blocked::NoConceptEvoked);
+    }
+
+    // Check for concept responses
+    for concept in &line.responds_to_concepts {
+        if let Some(Spoken(timestamps)) = facts.spoken_entity(facts.registry.concept(concept)) {
+            if let Some(max_time) = timestamps.iter().max() {
+                if now < *max_time + line.duration / 2 {
+                    continue;
+                }
+            }
+        }
+        return Some(Blocked::OutsideResponseWindow(concept.clone()));
+    }
+
+    // Check dialogue requirements and conflicts
+    let spoken = facts.spoken_entity(Some(entity));
+    if !line.repeatable && spoken.is_some() {
+        return Some(Blocked::NotRepeatable);
+    }
+    if let Some(max_repeats) = line.max_repeats {
+        let times = spoken.map_or(0, |Spoken(timestamps)| timestamps.len());
+        if times >= max_repeats {
+            return Some(Blocked::RepeatedEnough(times));
+        }
+    }
+    if let Some(max_time) = spoken.and_then(|Spoken(timestamps)| timestamps.iter().max()) {
+        if now < *max_time + line.cooldown {
+            return Some(Blocked::CoolingDown(*max_time + line.cooldown - now));
+        }
+    }
+    for dialogue in &line.requires_spoken {
+        if facts.spoken_entity(facts.registry.line(dialogue)).is_none() {
+            return Some(Blocked::NotYetSpoken(dialogue.clone()));
+        }
+    }
+    for dialogue in &line.conflicts_spoken {
+        if facts.spoken_entity(facts.registry.line(dialogue)).is_some() {
+            return Some(Blocked::ConflictSpoken(dialogue.clone()));
+        }
+    }
+
+    if let Some(idle_after) = line.idle_after {
+        if idle < idle_after {
+            return Some(Blocked::NotIdle(idle_after - idle));
+        }
+    }
+
+    if let Some(condition) = &line.condition {
+        if !condition.holds(facts) {
+            return Some(Blocked::ConditionFails);
+        }
+    }
+
+    None
+}
+
+/// The line spoken in place of `line` if it has been spoken enough times to escalate, or why it
+/// cannot be spoken. `available` finds lines that are free to start.
+pub(crate) fn escalation<'l>(
+    entity: Entity,
+    line: &Line,
+    available: impl Fn(Entity) -> Option<&'l Line>,
+    facts: &DialogueFacts,
+    now: Duration,
+    idle: Duration,
+) -> Result<Option<(Entity, &'l Line)>, Blocked> {
+    let escalation = match &line.escalation {
+        Some(escalation) => escalation,
+        None => return Ok(None),
+    };
+    let times = facts.spoken_entity(Some(entity)).map_or(0, |Spoken(timestamps)| timestamps.len());
+    if times < escalation.after {
+        return Ok(None)
+    }
+
+    let escalated = facts.registry
+        .line(&escalation.line)
+        .and_then(|e| available(e).map(|line| (e, line)))
+        .filter(|(_, escalated)| escalated.track == line.track)
+        .ok_or_else(|| Blocked::EscalationUnavailable(escalation.line.clone()))?;
+    match blocked(escalated.0, escalated.1, facts, now, idle) {
+        Some(reason) => Err(Blocked::EscalationBlocked(escalation.line.clone(), Box::new(reason))),
+        None => Ok(Some(escalated)),
+    }
+}
+
+/// Everything a line can set off as it starts and ends.
+#[derive(SystemParam)]
+pub struct LineEffects<'a> {
+    play_audio: EventWriter<'a, PlayAudioEvent>,
+    stop_audio: EventWriter<'a, StopAudioEvent>,
+    start_animation: EventWriter<'a, AnimationStartEvent>,
+    end_animation: EventWriter<'a, AnimationEndEvent>,
+    set_question: EventWriter<'a, SetQuestionEvent>,
+    clear_question: EventWriter<'a, ClearQuestionEvent>,
+    started: EventWriter<'a, LineStartedEvent>,
+    finished: EventWriter<'a, LineFinishedEvent>,
+    act_finished: EventWriter<'a, ActFinishedEvent>,
+}
+
+/// Stop speaking a line, which may yet be resumed.
+fn stop_line(
+    entity: Entity,
+    line: &Line,
+    registry: &Registry,
+    characters: &Query<&Character>,
+    commands: &mut Commands,
+    effects: &mut LineEffects,
+) {
+    commands
+        .entity(entity)
+        .remove::<Speaking>();
+
+    // End animations
+    for animation in talk_animations(&line.speakers, registry, characters) {
+        info!("End {}", animation);
+        effects.end_animation.send(AnimationEndEvent(animation));
+    }
+    for animation in &line.animations {
+        info!("End {}", animation);
+        effects.end_animation.send(AnimationEndEvent(animation.clone()));
+    }
+    for animation in &line.ends_animations {
+        info!("End {}", animation);
+        effects.end_animation.send(AnimationEndEvent(animation.clone()));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn end_line(
+    entity: Entity,
+    line: &Line,
+    skipped: bool,
+    ids: &Query<&LineId>,
+    registry: &Registry,
+    characters: &Query<&Character>,
+    commands: &mut Commands,
+    effects: &mut LineEffects,
+) {
+    stop_line(entity, line, registry, characters, commands, effects);
+
+    if let Ok(id) = ids.get(entity) {
+        effects.finished.send(LineFinishedEvent { line: entity, id: id.clone(), skipped });
+    }
+
+    if line.clear_question {
+        effects.clear_question.send(ClearQuestionEvent);
+    }
+
+    if line.ends_act {
+        info!("End of act");
+        effects.act_finished.send(ActFinishedEvent);
+    }
+}
+
+fn start_animations(
+    line: &Line,
+    registry: &Registry,
+    characters: &Query<&Character>,
+    effects: &mut LineEffects,
+) {
+    // Talking first, so that other animations can override it
+    for animation in talk_animations(&line.speakers, registry, characters) {
+        info!("Start {}", animation);
+        effects.start_animation.send(AnimationStartEvent(animation));
+    }
+    for animation in &line.animations {
+        info!("Start {}", animation);
+        effects.start_animation.send(AnimationStartEvent(animation.clone()));
+    }
+    for animation in &line.starts_animations {
+        info!("Start {}", animation);
+        effects.start_animation.send(AnimationStartEvent(animation.clone()));
+    }
+}
+
+/// Resuming a line carries on the same telling of it, rather than counting as another.
+fn record_resumed(entity: Entity, spoken_query: &Query<&Spoken>, commands: &mut Commands, started: Duration) {
+    let mut timestamps = spoken_query.get(entity).map_or(vec![], |Spoken(timestamps)| timestamps.clone());
+    timestamps.pop();
+    timestamps.push(started);
+    commands
+        .entity(entity)
+        .insert(Spoken(timestamps));
+}
+
+fn record_spoken(entity: Entity, spoken_query: &Query<&Spoken>, commands: &mut Commands, now: Duration) {
+    if let Ok(Spoken(timestamps)) = spoken_query.get(entity) {
+        let mut timestamps = timestamps.clone();
+        timestamps.push(now);
+        commands
+            .entity(entity)
+            .insert(Spoken(timestamps));
+    } else {
+        commands
+            .entity(entity)
+            .insert(Spoken(vec![now]));
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn progress_dialogue(
     speaking_query: Query<(Entity, &Line, &Spoken), With<Speaking>>,
-    lines_query: Query<(Entity, &Line)>,
+    lines_query: Query<(Entity, &Line), (Without<AutoDuration>, Without<Interrupted>)>,
+    interrupted_query: Query<(Entity, &Line, &Interrupted)>,
     spoken_query: Query<&Spoken>,
     concept_query: Query<Entity, With<Evoked>>,
-    time: Res<Time>,
+    characters: Query<&Character>,
+    ids: Query<&LineId>,
+    registry: Res<Registry>,
+    clock: Res<GameClock>,
+    idle: Res<IdleTimer>,
+    mut story: ResMut<StoryState>,
     mut commands: Commands,
-    mut ev_play: EventWriter<PlayAudioEvent>,
-    mut ev_stop: EventWriter<StopAudioEvent>,
-    mut start_event_writer: EventWriter<AnimationStartEvent>,
-    mut end_event_writer: EventWriter<AnimationEndEvent>,
-    mut clear_question_event_writer: EventWriter<ClearQuestionEvent>,
-    mut set_question_event_writer: EventWriter<SetQuestionEvent>,
+    mut effects: LineEffects,
+    mut ev_advance: EventReader<AdvanceEvent>,
 ) {
-    // If a line is currently being spoken, check if it is done
-    if let Some((entity, line, Spoken(timestamps))) = speaking_query.iter().next() {
-        if let Some(recent_timestamp) = timestamps.iter().max() {
-            if time.time_since_startup() > *recent_timestamp + line.duration {
-                commands
-                    .entity(entity)
-                    .remove::<Speaking>();
+    let advance = ev_advance.iter().count() > 0;
 
-                // End animations
-                for animation in &line.animations {
-                    info!("End {}", animation);
-                    end_event_writer.send(AnimationEndEvent(animation.clone()));
-                }
-                for animation in &line.ends_animations {
-                    info!("End {}", animation);
-                    end_event_writer.send(AnimationEndEvent(animation.clone()));
-                }
+    // Nothing moves on while the game is paused, not even by skipping
+    if clock.is_paused() {
+        return
+    }
 
-                if line.clear_question {
-                    clear_question_event_writer.send(ClearQuestionEvent);
-                }
-            }
+    let available = |entity: Entity| lines_query.get(entity).ok().map(|(_, line)| line);
 
-            return
-        }
-    }
+    // Lines only spoken in place of others
+    let escalations: Vec<Entity> = lines_query
+        .iter()
+        .filter_map(|(_, line)| line.escalation.as_ref())
+        .filter_map(|escalation| registry.line(&escalation.line))
+        .collect();
 
-    // If no lines are being spoken, and there is a line that can be spoken, say it
-    if let Some((entity, line)) = lines_query
+    // Each track speaks one line at a time, independently of the others
+    let mut tracks: Vec<&TrackId> = lines_query
         .iter()
-        .filter(|(entity, line)| {
-            // Check concept requirements
-            for concept in &line.requires_concepts {
-                if let Err(_) = concept_query.get(*concept) {
-                    return false;
+        .map(|(_, line)| &line.track)
+        .chain(speaking_query.iter().map(|(_, line, _)| &line.track))
+        .chain(interrupted_query.iter().map(|(_, line, _)| &line.track))
+        .collect();
+    tracks.sort();
+    tracks.dedup();
+
+    for track in tracks {
+        // If a line is currently being spoken, check if it is done or can be interrupted
+        let mut current = None;
+        if let Some((entity, line, Spoken(timestamps))) = speaking_query.iter().find(|(_, line, _)| line.track == *track) {
+            if let Some(recent_timestamp) = timestamps.iter().max() {
+                if advance && *track == TrackId::main() {
+                    info!("Skipped line");
+                    if line.audio.is_some() {
+                        effects.stop_audio.send(StopAudioEvent { channel: Channel::Dialogue(track.clone()) } );
+                    }
+                    end_line(entity, line, true, &ids, &registry, &characters, &mut commands, &mut effects);
+                    continue
+                } else if clock.elapsed() > *recent_timestamp + line.duration {
+                    end_line(entity, line, false, &ids, &registry, &characters, &mut commands, &mut effects);
+                    continue
+                } else if !line.interruptible {
+                    continue
                 }
+                current = Some((entity, line, clock.elapsed() - *recent_timestamp));
             }
+        }
 
-            for concept in &line.responds_to_concepts {
-                if let Err(_) = concept_query.get(*concept) {
-                    return false;
+        // Once nothing is being spoken, resume the line cut off most recently
+        if current.is_none() {
+            if let Some((entity, line, interrupted)) = interrupted_query
+                .iter()
+                .filter(|(_, line, _)| line.track == *track)
+                .max_by_key(|(.., i)| i.at)
+            {
+                let started = match (line.resume, &line.audio) {
+                    (Resume::Continue, None) => clock.elapsed() - interrupted.spoken_for,
+                    _ => clock.elapsed(),
+                };
+                info!("Resumed line");
+                record_resumed(entity, &spoken_query, &mut commands, started);
+                commands
+                    .entity(entity)
+                    .remove::<Interrupted>()
+                    .insert(Speaking);
+                if let Ok(id) = ids.get(entity) {
+                    effects.started.send(LineStartedEvent { line: entity, id: id.clone() });
                 }
-            }
 
-            if line.requires_any_concept {
-                if let None = concept_query.iter().next() {
-                    return false
+                if let Some(audio_handle) = &line.audio {
+                    effects.play_audio.send(PlayAudioEvent { channel: Channel::Dialogue(track.clone()), handle: audio_handle.clone() } );
+                }
+                start_animations(line, &registry, &characters, &mut effects);
+                if let Some(question) = &line.question {
+                    effects.set_question.send(SetQuestionEvent(question.clone()));
                 }
+                continue
             }
+        }
+
+        let facts = DialogueFacts {
+            registry: &registry,
+            spoken_query: &spoken_query,
+            concept_query: &concept_query,
+            story: &story,
+        };
+
+        // If no lines are being spoken, or the line being spoken can be interrupted,
+        // and there is a line that can be spoken, say it
+        if let Some((entity, line)) = lines_query
+            .iter()
+            .filter(|(entity, line)| {
+                if line.track != *track || escalations.contains(entity) {
+                    return false;
+                }
 
-            // Check for concept responses
-            for concept in &line.responds_to_concepts {
-                if let Ok(Spoken(timestamps)) = spoken_query.get(*concept) {
-                    if let Some(max_time) = timestamps.iter().max() {
-                        if time.time_since_startup() < *max_time + line.duration / 2 {
-                            continue;
-                        }
+                if let Some((current, current_line, _)) = current {
+                    if *entity == current
+                        || line.priority <= current_line.priority
+                        || line.priority < current_line.interrupt_priority
+                    {
+                        return false;
                     }
                 }
-                return false;
-            }
 
-            // Check dialogue requirements and conflicts
-            if !line.repeatable {
-                if let Ok(_) = spoken_query.get(*entity) {
-                    return false;
+                let idle = idle.idle(clock.elapsed());
+                blocked(*entity, line, &facts, clock.elapsed(), idle).is_none()
+                    && escalation(*entity, line, available, &facts, clock.elapsed(), idle).is_ok()
+            })
+            .max_by_key(|(_, line)| line.priority)
+        {
+            // Cut off the line being spoken
+            if let Some((current, current_line, spoken_for)) = current {
+                info!("Interrupted line");
+                if current_line.audio.is_some() {
+                    effects.stop_audio.send(StopAudioEvent { channel: Channel::Dialogue(track.clone()) } );
                 }
-            }
-            for dialogue in &line.requires_spoken {
-                if let Err(_) = spoken_query.get(*dialogue) {
-                    return false;
+                if current_line.resume == Resume::Never {
+                    end_line(current, current_line, true, &ids, &registry, &characters, &mut commands, &mut effects);
+                } else {
+                    // The line only ends once it is resumed and finished
+                    stop_line(current, current_line, &registry, &characters, &mut commands, &mut effects);
+                    commands
+                        .entity(current)
+                        .insert(Interrupted { at: clock.elapsed(), spoken_for });
                 }
             }
-            for dialogue in &line.conflicts_spoken {
-                if let Ok(_) = spoken_query.get(*dialogue) {
-                    return false;
+
+            // Escalate once the line has been repeated enough, still counting it as spoken
+            let idle = idle.idle(clock.elapsed());
+            let (entity, line) = match escalation(entity, line, available, &facts, clock.elapsed(), idle) {
+                Ok(Some(escalated)) => {
+                    info!("Escalated line");
+                    record_spoken(entity, &spoken_query, &mut commands, clock.elapsed());
+                    escalated
                 }
-            }
+                _ => (entity, line),
+            };
 
-            return true;
-        })
-        .max_by_key(|(_, line)| line.priority)
-    {
-        // Update line
-        if let Ok(Spoken(timestamps)) = spoken_query.get(entity) {
-            let mut timestamps = timestamps.clone();
-            timestamps.push(time.time_since_startup());
-            commands
-                .entity(entity)
-                .insert(Spoken(timestamps))
-                .insert(Speaking);
-        } else {
+            // Update line
+            record_spoken(entity, &spoken_query, &mut commands, clock.elapsed());
             commands
                 .entity(entity)
-                .insert(Spoken(vec![time.time_since_startup()]))
                 .insert(Speaking);
-        }
+            if let Ok(id) = ids.get(entity) {
+                effects.started.send(LineStartedEvent { line: entity, id: id.clone() });
+            }
 
-        // Play audio
-        if let Some(audio_handle) = &line.audio {
-            ev_play.send(PlayAudioEvent { channel: Channel::Dialogue, handle: audio_handle.clone() } );
-        }
-        if let Some(music_handle) = &line.music {
-            ev_play.send(PlayAudioEvent { channel: Channel::Music, handle: music_handle.clone() } );
-        }
-        if line.stop_audio {
-            ev_stop.send(StopAudioEvent { channel: Channel::Dialogue } );
-        }
-        if line.stop_music {
-            ev_stop.send(StopAudioEvent { channel: Channel::Music } );
-        }
+            // Play audio
+            if let Some(audio_handle) = &line.audio {
+                effects.play_audio.send(PlayAudioEvent { channel: Channel::Dialogue(track.clone()), handle: audio_handle.clone() } );
+            }
+            if let Some(music_handle) = &line.music {
+                effects.play_audio.send(PlayAudioEvent { channel: Channel::Music, handle: music_handle.clone() } );
+            }
+            if line.stop_audio {
+                effects.stop_audio.send(StopAudioEvent { channel: Channel::Dialogue(track.clone()) } );
+            }
+            if line.stop_music {
+                effects.stop_audio.send(StopAudioEvent { channel: Channel::Music } );
+            }
 
-        // Update groups
-        for group in &line.groups {
-            if let Ok(Spoken(timestamps)) = spoken_query.get(*group) {
-                let mut timestamps = timestamps.clone();
-                timestamps.push(time.time_since_startup());
-                commands
-                    .entity(*group)
-                    .insert(Spoken(timestamps));
-            } else {
-                commands
-                    .entity(*group)
-                    .insert(Spoken(vec![time.time_since_startup()]));
+            // Update groups
+            for group in line.groups.iter().filter_map(|id| registry.line(id)) {
+                record_spoken(group, &spoken_query, &mut commands, clock.elapsed());
             }
-        }
 
-        // Start animations
-        for animation in &line.animations {
-            info!("Start {}", animation);
-            start_event_writer.send(AnimationStartEvent(animation.clone()));
-        }
-        for animation in &line.starts_animations {
-            info!("Start {}", animation);
-            start_event_writer.send(AnimationStartEvent(animation.clone()));
-        }
+            start_animations(line, &registry, &characters, &mut effects);
 
-        if let Some(question) = &line.question {
-            set_question_event_writer.send(SetQuestionEvent(question.clone()));
-        }
+            if let Some(question) = &line.question {
+                effects.set_question.send(SetQuestionEvent(question.clone()));
+            }
 
-        // Consume concepts
-        for concept in &line.consumes_concepts {
-            commands
-                .entity(*concept)
-                .remove::<Evoked>();
-        }
-        if line.consumes_all_concepts {
-            for concept in concept_query.iter() {
+            // Update the story
+            for (name, value) in &line.sets {
+                story.set(name, value.clone());
+            }
+            for (name, amount) in &line.increments {
+                story.increment(name, *amount);
+            }
+
+            // Consume concepts
+            for concept in line.consumes_concepts.iter().filter_map(|id| registry.concept(id)) {
                 commands
                     .entity(concept)
                     .remove::<Evoked>();
             }
-        }
-    }
-
-}
-
-fn dialogue_startup(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-) {
-    commands
-        .spawn_bundle(UiCameraBundle::default());
-
-    commands
-        .spawn_bundle(TextBundle {
-            style: Style {
-                align_self: AlignSelf::Center,
-                align_items: AlignItems::Center,
-                align_content: AlignContent::Center,
-                position_type: PositionType::Absolute,
-                position: Rect {
-                    bottom: Val::Px(5.0),
-                    left: Val::Px(15.0),
-                    right: Val::Px(15.0),
-                    ..Default::default()
-                },
-                max_size: Size {
-                    width: Val::Px(1280. - 2. * 15.),
-                    height: Val::Px(200.0),
-                },
-                ..Default::default()
-            },
-            ..Default::default()
-        })
-        .insert(TextBox {
-            style: TextStyle {
-                font: asset_server.load("GloriaHallelujah-Regular.ttf"),
-                font_size: 30.0,
-                color: Color::WHITE,
-            },
-            alignment: TextAlignment {
-                horizontal: HorizontalAlign::Left,
-                vertical: VerticalAlign::Top,
+            if line.consumes_all_concepts {
+                for concept in concept_query.iter() {
+                    commands
+                        .entity(concept)
+                        .remove::<Evoked>();
+                }
             }
-        });
-}
-
-struct TextBox {
-    style: TextStyle,
-    alignment: TextAlignment,
-}
-
-fn render_lines(
-    mut commands: Commands,
-    text: Query<(Entity, &TextBox)>,
-    lines: Query<&Line, With<Speaking>>,
-) {
-    if let Some(line) = lines.iter().next() {
-        for (e, t) in text.iter() {
-            commands
-                .entity(e)
-                .insert(Text::with_section(
-                    &line.text,
-                    t.style.clone(),
-                    t.alignment,
-                ));
         }
     }
 }
@@ -309,10 +700,23 @@ pub struct DialoguePlugin;
 
 impl Plugin for DialoguePlugin {
     fn build(&self, app: &mut AppBuilder) {
-        app.add_system(progress_dialogue.system())
+        let audio_io = AudioIo(create_platform_default_asset_io(app).into());
+        app.init_resource::<AutoDurationSettings>()
+            .insert_resource(audio_io)
+            .init_resource::<StoryState>()
+            .init_resource::<DialogueRng>()
+            .init_resource::<IdleTimer>()
+            .add_system(track_activity.system().before("progress_dialogue"))
+            .add_system(progress_dialogue.system().label("progress_dialogue"))
+            .add_system(measure_audio.system())
+            .add_system(apply_measured_audio.system())
+            // Once the update stage's commands have ended lines
+            .add_system_to_stage(CoreStage::PostUpdate, choose_variants.system())
+            .add_event::<AdvanceEvent>()
+            .add_event::<LineStartedEvent>()
+            .add_event::<LineFinishedEvent>()
+            .add_event::<ActFinishedEvent>()
             .add_event::<AnimationStartEvent>()
             .add_event::<AnimationEndEvent>();
-        app.add_startup_system(dialogue_startup.system());
-        app.add_system(render_lines.system());
     }
 }
@@ -0,0 +1,342 @@
+use crate::characters::{speaker_names, Character};
+use crate::clock::GameClock;
+use crate::dialogue::{AdvanceEvent, Line, Speaking, Spoken, TrackId};
+use crate::markup::{self, Span};
+use crate::registry::Registry;
+use bevy::prelude::{
+    Commands, CoreStage, Plugin, AppBuilder, IntoSystem, TextBundle, Style, AlignSelf,
+    PositionType, Rect, Val, Text, TextStyle, Color, TextAlignment,
+    HorizontalAlign, VerticalAlign, Res, AssetServer, Query, With, UiCameraBundle,
+    Entity, Size, AlignContent, AlignItems, Input, MouseButton, KeyCode, EventWriter,
+    ParallelSystemDescriptorCoercion, TextSection, Added, RemovedComponents,
+};
+use bevy_interact_2d::InteractionState;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Where a track's text box sits, in pixels from the edges of the window.
+#[derive(Clone, Deserialize)]
+pub struct TextBoxPlacement {
+    #[serde(default)]
+    pub top: Option<f32>,
+    #[serde(default)]
+    pub bottom: Option<f32>,
+    #[serde(default)]
+    pub left: Option<f32>,
+    #[serde(default)]
+    pub right: Option<f32>,
+    #[serde(default = "default_font_size")]
+    pub font_size: f32,
+}
+
+fn default_font_size() -> f32 {
+    30.
+}
+
+impl TextBoxPlacement {
+    /// Along the bottom of the window, where the main conversation is shown.
+    fn main() -> TextBoxPlacement {
+        TextBoxPlacement {
+            top: None,
+            bottom: Some(5.),
+            left: Some(15.),
+            right: Some(15.),
+            font_size: default_font_size(),
+        }
+    }
+}
+
+fn spawn_text_box(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    track: TrackId,
+    placement: &TextBoxPlacement,
+) {
+    let px = |edge: Option<f32>| edge.map_or(Val::Undefined, Val::Px);
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                align_self: AlignSelf::Center,
+                align_items: AlignItems::Center,
+                align_content: AlignContent::Center,
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: px(placement.top),
+                    bottom: px(placement.bottom),
+                    left: px(placement.left),
+                    right: px(placement.right),
+                },
+                max_size: Size {
+                    width: Val::Px(1280. - placement.left.unwrap_or(0.) - placement.right.unwrap_or(0.)),
+                    height: Val::Px(200.0),
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(TextBox {
+            track,
+            style: TextStyle {
+                font: asset_server.load("GloriaHallelujah-Regular.ttf"),
+                font_size: placement.font_size,
+                color: Color::WHITE,
+            },
+            alignment: TextAlignment {
+                horizontal: HorizontalAlign::Left,
+                vertical: VerticalAlign::Top,
+            },
+            completed: None,
+        });
+}
+
+fn dialogue_startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    commands
+        .spawn_bundle(UiCameraBundle::default());
+
+    spawn_text_box(&mut commands, &asset_server, TrackId::main(), &TextBoxPlacement::main());
+}
+
+/// Give every other track a text box of its own once the scene places it.
+fn spawn_track_text_boxes(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    tracks: Query<(&TrackId, &TextBoxPlacement), Added<TextBoxPlacement>>,
+) {
+    for (track, placement) in tracks.iter() {
+        spawn_text_box(&mut commands, &asset_server, track.clone(), placement);
+    }
+}
+
+/// Take away the text boxes of tracks that have gone with the end of their scene.
+fn despawn_track_text_boxes(
+    mut commands: Commands,
+    removed: RemovedComponents<TextBoxPlacement>,
+    tracks: Query<&TrackId, With<TextBoxPlacement>>,
+    text_boxes: Query<(Entity, &TextBox)>,
+) {
+    if removed.iter().next().is_none() {
+        return
+    }
+    let remaining: Vec<&TrackId> = tracks.iter().collect();
+    for (entity, text_box) in text_boxes.iter() {
+        if text_box.track != TrackId::main() && !remaining.contains(&&text_box.track) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+struct TextBox {
+    track: TrackId,
+    style: TextStyle,
+    alignment: TextAlignment,
+    /// A line, and when it started, whose text has been revealed in full by clicking
+    completed: Option<(Entity, Duration)>,
+}
+
+/// How marked up parts of a line stand out from the rest.
+pub struct MarkupStyle {
+    /// Stage directions, written `*like this*`
+    pub action_color: Color,
+    /// Emphasis, written `[em]like this[/em]`
+    pub emphasis_color: Color,
+}
+
+impl Default for MarkupStyle {
+    fn default() -> MarkupStyle {
+        MarkupStyle {
+            action_color: Color::rgb(0.6, 0.6, 0.6),
+            emphasis_color: Color::GOLD,
+        }
+    }
+}
+
+/// The sections to show for the first `visible` characters of some marked up text.
+fn styled_sections(text: &str, visible: usize, style: &TextStyle, markup: &MarkupStyle) -> Vec<TextSection> {
+    let mut remaining = visible;
+    let mut sections = vec![];
+    for span in markup::parse(text) {
+        if remaining == 0 {
+            break;
+        }
+        let value: String = span.text.chars().take(remaining).collect();
+        remaining -= value.chars().count();
+
+        let color = match span {
+            Span { color: Some(color), .. } => color,
+            Span { emphasis: true, .. } => markup.emphasis_color,
+            Span { action: true, .. } => markup.action_color,
+            _ => style.color,
+        };
+        sections.push(TextSection {
+            value,
+            style: TextStyle { color, ..style.clone() },
+        });
+    }
+    sections
+}
+
+/// How quickly the text of a line is revealed.
+pub struct Typewriter {
+    pub characters_per_second: f32,
+    /// Extra seconds to wait after punctuation
+    pub punctuation_pause: f32,
+}
+
+impl Default for Typewriter {
+    fn default() -> Typewriter {
+        Typewriter {
+            characters_per_second: 40.,
+            punctuation_pause: 0.3,
+        }
+    }
+}
+
+impl Typewriter {
+    /// The number of characters of `line` to show `elapsed` seconds after it started.
+    fn visible_characters(&self, line: &Line, elapsed: f32) -> usize {
+        let delays: Vec<f32> = markup::plain(&line.text)
+            .chars()
+            .map(|c| match c {
+                '.' | ',' | '!' | '?' | ';' | ':' | '…' => 1. / self.characters_per_second + self.punctuation_pause,
+                _ => 1. / self.characters_per_second,
+            })
+            .collect();
+
+        // Voiced lines are paced to finish with their audio
+        let total: f32 = delays.iter().sum();
+        let scale = match line.audio {
+            Some(_) if total > 0. => line.duration.as_secs_f32() / total,
+            _ => 1.,
+        };
+
+        let mut time = 0.;
+        delays
+            .iter()
+            .take_while(|delay| {
+                time += *delay * scale;
+                time <= elapsed
+            })
+            .count()
+    }
+}
+
+/// A left click on nothing the ghost can interact with, which would otherwise send it there.
+fn clicked_background(mouse_button_input: &Input<MouseButton>, interaction_state: &InteractionState) -> bool {
+    mouse_button_input.just_pressed(MouseButton::Left)
+        && interaction_state.ordered_interact_list_map.values().all(|things| things.is_empty())
+}
+
+/// The least time each line is shown for while fast-forwarding.
+const FAST_FORWARD_MINIMUM: Duration = Duration::from_millis(300);
+
+/// Space or enter ends the current line of the main track, as does clicking away from any object
+/// once its text is fully revealed. Holding tab fast-forwards through lines, stopping at any that
+/// asks the player a question.
+#[allow(clippy::too_many_arguments)]
+fn advance_input(
+    text: Query<&TextBox>,
+    lines: Query<(Entity, &Line, &Spoken), With<Speaking>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    interaction_state: Res<InteractionState>,
+    clock: Res<GameClock>,
+    mut ev_advance: EventWriter<AdvanceEvent>,
+) {
+    // Clicks and keys belong to the pause menu while it is open
+    if clock.is_paused() {
+        return
+    }
+
+    let speaking = lines
+        .iter()
+        .find(|(_, line, _)| line.track == TrackId::main())
+        .map(|(entity, line, Spoken(timestamps))| (entity, line, timestamps.iter().max().copied().unwrap_or_default()));
+    let revealed = speaking
+        .map_or(false, |(entity, _, started)| text.iter().any(|t| t.completed == Some((entity, started))));
+    let fast_forward = keyboard_input.pressed(KeyCode::Tab)
+        && speaking.map_or(false, |(_, line, started)| {
+            line.question.is_none() && clock.elapsed() - started >= FAST_FORWARD_MINIMUM
+        });
+
+    if keyboard_input.just_pressed(KeyCode::Space)
+        || keyboard_input.just_pressed(KeyCode::Return)
+        || fast_forward
+        || (revealed && clicked_background(&mouse_button_input, &interaction_state))
+    {
+        ev_advance.send(AdvanceEvent);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn render_lines(
+    mut text: Query<(&mut TextBox, &mut Text)>,
+    lines: Query<(Entity, &Line, &Spoken), With<Speaking>>,
+    characters: Query<&Character>,
+    registry: Res<Registry>,
+    typewriter: Res<Typewriter>,
+    markup: Res<MarkupStyle>,
+    clock: Res<GameClock>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    interaction_state: Res<InteractionState>,
+) {
+    let clicked = !clock.is_paused() && clicked_background(&mouse_button_input, &interaction_state);
+    for (mut text_box, mut text) in text.iter_mut() {
+        let speaking = lines.iter().find(|(_, line, _)| line.track == text_box.track);
+        if let Some((entity, line, Spoken(timestamps))) = speaking {
+            let started = timestamps.iter().max().copied().unwrap_or_default();
+            let elapsed = (clock.elapsed() - started).as_secs_f32();
+
+            if clicked && text_box.track == TrackId::main() {
+                text_box.completed = Some((entity, started));
+            }
+            let length = markup::plain(&line.text).chars().count();
+            if typewriter.visible_characters(line, elapsed) >= length {
+                text_box.completed = Some((entity, started));
+            }
+
+            let shown = if text_box.completed == Some((entity, started)) {
+                length
+            } else {
+                typewriter.visible_characters(line, elapsed)
+            };
+
+            text.alignment = text_box.alignment;
+            text.sections.clear();
+
+            // Name plate, in the colour of whoever speaks first
+            if let Some(name) = speaker_names(&line.speakers, &registry, &characters) {
+                let color = line.speakers
+                    .first()
+                    .and_then(|id| registry.character(id))
+                    .and_then(|entity| characters.get(entity).ok())
+                    .map_or(text_box.style.color, |character| character.color);
+                text.sections.push(TextSection {
+                    value: format!("{}: ", name),
+                    style: TextStyle { color, ..text_box.style.clone() },
+                });
+            }
+            text.sections.extend(styled_sections(&line.text, shown, &text_box.style, &markup));
+        } else {
+            // Nothing left over from a line that has ended
+            text.sections.clear();
+            text_box.completed = None;
+        }
+    }
+}
+
+pub struct DialogueDisplayPlugin;
+
+impl Plugin for DialogueDisplayPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Typewriter>()
+            .init_resource::<MarkupStyle>()
+            .add_startup_system(dialogue_startup.system())
+            .add_system(spawn_track_text_boxes.system())
+            .add_system_to_stage(CoreStage::PostUpdate, despawn_track_text_boxes.system())
+            .add_system(advance_input.system().before("render_lines"))
+            .add_system(render_lines.system().label("render_lines"));
+    }
+}
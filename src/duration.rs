@@ -0,0 +1,190 @@
+use crate::dialogue::Line;
+use crate::markup;
+use bevy::asset::{AssetIo, LoadState};
+use bevy::prelude::{AssetServer, Commands, Entity, Query, Res, With, Without, warn};
+use bevy::tasks::IoTaskPool;
+use std::ffi::OsStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Marks a line whose duration is taken from the length of its voice audio, or estimated from
+/// its text when it is silent. The line is not spoken until its duration is known.
+pub struct AutoDuration;
+
+pub struct AutoDurationSettings {
+    /// Time to leave a voiced line on screen after its audio ends
+    pub tail: Duration,
+    /// Reading speed assumed for silent lines
+    pub characters_per_second: f32,
+}
+
+impl Default for AutoDurationSettings {
+    fn default() -> AutoDurationSettings {
+        AutoDurationSettings {
+            tail: Duration::from_secs(1),
+            characters_per_second: 15.,
+        }
+    }
+}
+
+impl AutoDurationSettings {
+    fn reading_time(&self, text: &str) -> Duration {
+        Duration::from_secs_f32(markup::plain(text).chars().count() as f32 / self.characters_per_second) + self.tail
+    }
+}
+
+/// Bitrates in kbit/s by index, for MPEG-1 layers I to III, then MPEG-2 layer I and layers II and III.
+const BITRATES: [[u32; 15]; 5] = [
+    [0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448],
+    [0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384],
+    [0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320],
+    [0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256],
+    [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+];
+
+/// The samples in an mp3 frame and its length in bytes, if `header` starts one.
+fn mp3_frame(header: u32) -> Option<(u32, u32, usize)> {
+    if header >> 21 != 0x7ff {
+        return None
+    }
+    // 0 is MPEG-2.5, 2 is MPEG-2 and 3 is MPEG-1
+    let version = (header >> 19) & 3;
+    // 1 is layer III, 2 is layer II and 3 is layer I
+    let layer = (header >> 17) & 3;
+    let bitrate = ((header >> 12) & 0xf) as usize;
+    let sample_rate = ((header >> 10) & 3) as usize;
+    let padding = (header >> 9) & 1;
+    if version == 1 || layer == 0 || bitrate == 0 || bitrate == 0xf || sample_rate == 3 {
+        return None
+    }
+
+    let sample_rate = [44100, 48000, 32000][sample_rate] >> (3 - version).min(2);
+    let bitrate = match (version, layer) {
+        (3, layer) => BITRATES[(3 - layer) as usize][bitrate],
+        (_, 3) => BITRATES[3][bitrate],
+        _ => BITRATES[4][bitrate],
+    } * 1000;
+    let (samples, length) = match (version, layer) {
+        (_, 3) => (384, (12 * bitrate / sample_rate + padding) * 4),
+        (3, _) | (_, 2) => (1152, 144 * bitrate / sample_rate + padding),
+        _ => (576, 72 * bitrate / sample_rate + padding),
+    };
+    Some((samples, sample_rate, length as usize))
+}
+
+/// How long mp3 audio plays for, counted from the headers of its frames without decoding them.
+pub fn mp3_length(bytes: &[u8]) -> Option<Duration> {
+    // Skip any ID3v2 tag, whose size is stored in the low seven bits of four bytes
+    let mut i = match bytes {
+        [b'I', b'D', b'3', _, _, flags, size @ ..] if size.len() >= 4 => {
+            let size = size[..4].iter().fold(0, |size, byte| size << 7 | (*byte as usize & 0x7f));
+            let footer = if flags & 0x10 != 0 { 10 } else { 0 };
+            10 + size + footer
+        }
+        _ => 0,
+    };
+
+    let mut samples = 0;
+    let mut rate = None;
+    while i + 4 <= bytes.len() {
+        let header = u32::from_be_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        match mp3_frame(header) {
+            Some((frame_samples, sample_rate, length)) if i + length <= bytes.len() => {
+                samples += frame_samples as u64;
+                rate.get_or_insert(sample_rate);
+                i += length;
+            }
+            // Not a frame, so look for the next one
+            _ => i += 1,
+        }
+    }
+    rate.map(|rate| Duration::from_secs_f64(samples as f64 / rate as f64))
+}
+
+/// Reads voice audio to measure it, the same way the asset server reads assets. The length of
+/// loaded audio and its loader are both private to bevy_kira_audio 0.5.
+pub(crate) struct AudioIo(pub(crate) Arc<dyn AssetIo>);
+
+enum Measured {
+    Reading,
+    Length(Duration),
+    Unmeasurable,
+}
+
+pub(crate) struct MeasuringAudio(Arc<Mutex<Measured>>);
+
+/// Times each line marked with `AutoDuration`. Voice audio is measured once it has loaded.
+#[allow(clippy::type_complexity)]
+pub(crate) fn measure_audio(
+    mut lines: Query<(Entity, &mut Line), (With<AutoDuration>, Without<MeasuringAudio>)>,
+    asset_server: Res<AssetServer>,
+    io: Res<AudioIo>,
+    task_pool: Res<IoTaskPool>,
+    settings: Res<AutoDurationSettings>,
+    mut commands: Commands,
+) {
+    for (entity, mut line) in lines.iter_mut() {
+        let handle = match &line.audio {
+            Some(handle) => handle,
+            None => {
+                line.duration = settings.reading_time(&line.text);
+                commands
+                    .entity(entity)
+                    .remove::<AutoDuration>();
+                continue
+            }
+        };
+
+        let path = match (asset_server.get_load_state(handle), asset_server.get_handle_path(handle)) {
+            (LoadState::Loaded, Some(path)) => path.path().to_path_buf(),
+            (LoadState::Failed, _) => {
+                line.duration = settings.reading_time(&line.text);
+                commands
+                    .entity(entity)
+                    .remove::<AutoDuration>();
+                continue
+            }
+            _ => continue,
+        };
+
+        let measured = Arc::new(Mutex::new(Measured::Reading));
+        let (io, result) = (io.0.clone(), measured.clone());
+        task_pool
+            .spawn(async move {
+                let length = match io.load_path(&path).await {
+                    Ok(bytes) if path.extension() == Some(OsStr::new("mp3")) => mp3_length(&bytes),
+                    Ok(_) => None,
+                    Err(e) => {
+                        warn!("Could not read {}: {}", path.display(), e);
+                        None
+                    }
+                };
+                if length.is_none() {
+                    warn!("Could not measure {}", path.display());
+                }
+                *result.lock().unwrap() = length.map_or(Measured::Unmeasurable, Measured::Length);
+            })
+            .detach();
+        commands
+            .entity(entity)
+            .insert(MeasuringAudio(measured));
+    }
+}
+
+pub(crate) fn apply_measured_audio(
+    mut lines: Query<(Entity, &mut Line, &MeasuringAudio)>,
+    settings: Res<AutoDurationSettings>,
+    mut commands: Commands,
+) {
+    for (entity, mut line, MeasuringAudio(measured)) in lines.iter_mut() {
+        line.duration = match *measured.lock().unwrap() {
+            Measured::Reading => continue,
+            Measured::Length(length) => length + settings.tail,
+            Measured::Unmeasurable => settings.reading_time(&line.text),
+        };
+        commands
+            .entity(entity)
+            .remove::<MeasuringAudio>()
+            .remove::<AutoDuration>();
+    }
+}
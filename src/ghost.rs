@@ -1,7 +1,8 @@
 use bevy::prelude::{
     Plugin, Res, Transform, Input, MouseButton, AppBuilder,
-    Entity, Vec3, Query, With, EventWriter, Time, IntoSystem,
+    Entity, Vec3, Query, With, EventWriter, IntoSystem,
 };
+use crate::clock::GameClock;
 use bevy_interact_2d::{InteractionPlugin, InteractionState};
 
 pub struct Clickable;
@@ -32,8 +33,9 @@ fn click(
     interaction_state: Res<InteractionState>,
     mut moveable_query: Query<&mut MoveTo>,
     target_query: Query<&Transform, With<Clickable>>,
+    clock: Res<GameClock>,
 ) {
-    if !mouse_button_input.just_pressed(MouseButton::Left) {
+    if clock.is_paused() || !mouse_button_input.just_pressed(MouseButton::Left) {
         return;
     }
 
@@ -50,11 +52,11 @@ fn click(
 }
 
 fn movement(
-    time: Res<Time>,
+    clock: Res<GameClock>,
     mut q: Query<(Entity, &mut MoveTo, &mut Transform)>,
     mut event_writer: EventWriter<GhostInteractionEvent>,
 ) {
-    let delta = time.delta_seconds();
+    let delta = clock.delta_seconds();
 
     for (ghost, mut move_to, mut t) in q.iter_mut() {
         if let Some((target, coords)) = move_to.target {
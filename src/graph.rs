@@ -0,0 +1,156 @@
+use crate::characters::CharacterId;
+use crate::concepts::ConceptId;
+use crate::dialogue::LineId;
+use crate::markup;
+use crate::scene::{LineDefinition, SceneDefinition};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+/// How much of a line's text its node shows.
+const SNIPPET_LENGTH: usize = 32;
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn line_node(LineId(id): &LineId) -> String {
+    format!("\"line:{}\"", escape(id))
+}
+
+fn concept_node(ConceptId(id): &ConceptId) -> String {
+    format!("\"concept:{}\"", escape(id))
+}
+
+fn snippet(line: &LineDefinition) -> String {
+    let text = match line.variants.first() {
+        Some(variant) if line.text.is_empty() => &variant.text,
+        _ => &line.text,
+    };
+    let text = markup::plain(text);
+    match text.char_indices().nth(SNIPPET_LENGTH) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text,
+    }
+}
+
+/// The scene's dialogue as a Graphviz graph. Lines lead on to the lines that require them
+/// (solid), or that they rule out (red), and concepts lead to the lines that need them (dashed).
+/// Objects lead to the concepts they evoke, and concepts to those they join to make.
+pub fn dot(scene: &SceneDefinition) -> String {
+    let names: HashMap<&CharacterId, &str> = scene.characters
+        .iter()
+        .map(|character| (&character.id, character.name.as_str()))
+        .collect();
+    let line_ids: HashSet<&LineId> = scene.lines.iter().map(|line| &line.id).collect();
+
+    let mut out = String::new();
+    writeln!(out, "digraph scene {{").unwrap();
+    writeln!(out, "    rankdir=LR;").unwrap();
+    writeln!(out, "    node [fontname=\"sans-serif\", fontsize=10];").unwrap();
+
+    // Nodes
+    for line in &scene.lines {
+        let speakers: Vec<&str> = line.speakers
+            .iter()
+            .map(|speaker| names.get(speaker).copied().unwrap_or(speaker.0.as_str()))
+            .collect();
+        let mut label = line.id.0.clone();
+        let text = snippet(line);
+        match (speakers.is_empty(), text.is_empty()) {
+            (_, true) => {}
+            (true, false) => write!(label, "\n{}", text).unwrap(),
+            (false, false) => write!(label, "\n{}: {}", speakers.join(" and "), text).unwrap(),
+        }
+        write!(label, "\npriority {}", line.priority).unwrap();
+        writeln!(out, "    {} [shape=box, label=\"{}\"];", line_node(&line.id), escape(&label).replace('\n', "\\n")).unwrap();
+    }
+    // Groups that are not lines themselves are spoken whenever one of their lines is
+    let mut groups: Vec<&LineId> = scene.lines
+        .iter()
+        .flat_map(|line| &line.groups)
+        .filter(|group| !line_ids.contains(group))
+        .collect();
+    groups.sort_by(|a, b| a.0.cmp(&b.0));
+    groups.dedup();
+    for group in groups {
+        writeln!(out, "    {} [shape=box, style=rounded, label=\"{}\"];", line_node(group), escape(&group.0)).unwrap();
+    }
+    for concept in &scene.concepts {
+        writeln!(
+            out,
+            "    {} [shape=ellipse, label=\"{}\\n{}\"];",
+            concept_node(&concept.id),
+            escape(&concept.id.0),
+            escape(&concept.description),
+        ).unwrap();
+    }
+    for sprite in scene.sprites.iter().filter(|sprite| sprite.evokes.is_some()) {
+        writeln!(out, "    \"object:{}\" [shape=house, label=\"{}\"];", escape(&sprite.name), escape(&sprite.name)).unwrap();
+    }
+
+    // Edges
+    for line in &scene.lines {
+        let node = line_node(&line.id);
+        for group in &line.groups {
+            writeln!(out, "    {} -> {} [style=dotted];", node, line_node(group)).unwrap();
+        }
+        for required in &line.requires_spoken {
+            writeln!(out, "    {} -> {};", line_node(required), node).unwrap();
+        }
+        for conflict in &line.conflicts_spoken {
+            writeln!(out, "    {} -> {} [color=red];", line_node(conflict), node).unwrap();
+        }
+        let mut concepts: Vec<&ConceptId> = line.requires_concepts
+            .iter()
+            .chain(&line.responds_to_concepts)
+            .collect();
+        if let Some(condition) = &line.condition {
+            let required = condition.required_lines();
+            let mut mentioned = condition.lines();
+            mentioned.sort_by(|a, b| a.0.cmp(&b.0));
+            mentioned.dedup();
+            for mentioned in mentioned {
+                match required.contains(&mentioned) {
+                    true => writeln!(out, "    {} -> {};", line_node(mentioned), node).unwrap(),
+                    false => writeln!(out, "    {} -> {} [style=dotted, label=\"condition\"];", line_node(mentioned), node).unwrap(),
+                }
+            }
+            concepts.extend(condition.concepts());
+        }
+        concepts.sort_by(|a, b| a.0.cmp(&b.0));
+        concepts.dedup();
+        for concept in concepts {
+            writeln!(out, "    {} -> {} [style=dashed];", concept_node(concept), node).unwrap();
+        }
+        if let Some(escalation) = &line.escalation {
+            writeln!(
+                out,
+                "    {} -> {} [style=bold, label=\"after {}\"];",
+                node,
+                line_node(&escalation.line),
+                escalation.after,
+            ).unwrap();
+        }
+    }
+    for sprite in &scene.sprites {
+        if let Some(concept) = &sprite.evokes {
+            writeln!(out, "    \"object:{}\" -> {};", escape(&sprite.name), concept_node(concept)).unwrap();
+        }
+    }
+    for concept in &scene.concepts {
+        for (i, parents) in concept.parents.iter().enumerate() {
+            // A recipe of several concepts joins them at a point first
+            let recipe = format!("\"recipe:{}:{}\"", escape(&concept.id.0), i);
+            writeln!(out, "    {} [shape=point];", recipe).unwrap();
+            writeln!(out, "    {} -> {};", recipe, concept_node(&concept.id)).unwrap();
+            let mut parents: Vec<&ConceptId> = parents.iter().collect();
+            parents.sort_by(|a, b| a.0.cmp(&b.0));
+            for parent in parents {
+                writeln!(out, "    {} -> {} [arrowhead=none];", concept_node(parent), recipe).unwrap();
+            }
+        }
+    }
+
+    writeln!(out, "}}").unwrap();
+    out
+}
@@ -0,0 +1,233 @@
+use crate::characters::{speaker_names, Character};
+use crate::clock::GameClock;
+use crate::dialogue::{Line, LineId, LineStartedEvent};
+use crate::markup;
+use crate::registry::Registry;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::{
+    AlignSelf, AppBuilder, AssetServer, Assets, BuildChildren, Color, ColorMaterial,
+    Commands, CoreStage, DespawnRecursiveExt, Entity, EventReader, HorizontalAlign, Input,
+    IntoSystem, KeyCode, NodeBundle, Plugin, PositionType, Query, Rect, Res, ResMut, Size, Style,
+    Text, TextAlignment, TextBundle, TextSection, TextStyle, Val, VerticalAlign, With, info, warn,
+};
+use std::time::Duration;
+
+/// A line as it was spoken.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryEntry {
+    pub time: Duration,
+    pub line: LineId,
+    pub speaker: Option<String>,
+    pub text: String,
+}
+
+/// Every line spoken so far, in order.
+#[derive(Default)]
+pub struct DialogueHistory {
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl DialogueHistory {
+    /// The history as plain text, one line per entry, leaving out silent pauses.
+    pub fn transcript(&self) -> String {
+        self.entries
+            .iter()
+            .filter(|entry| !entry.text.is_empty())
+            .map(|entry| format!("{}\n", format_entry(entry)))
+            .collect()
+    }
+}
+
+fn format_entry(entry: &HistoryEntry) -> String {
+    let seconds = entry.time.as_secs();
+    match &entry.speaker {
+        Some(speaker) => format!("[{:02}:{:02}] {}: {}", seconds / 60, seconds % 60, speaker, entry.text),
+        None => format!("[{:02}:{:02}] {}", seconds / 60, seconds % 60, entry.text),
+    }
+}
+
+fn record_history(
+    mut ev_started: EventReader<LineStartedEvent>,
+    lines: Query<&Line>,
+    characters: Query<&Character>,
+    registry: Res<Registry>,
+    clock: Res<GameClock>,
+    mut history: ResMut<DialogueHistory>,
+) {
+    for LineStartedEvent { line, id } in ev_started.iter() {
+        let line = match lines.get(*line) {
+            Ok(line) => line,
+            Err(_) => continue,
+        };
+        history.entries.push(HistoryEntry {
+            time: clock.elapsed(),
+            line: id.clone(),
+            speaker: speaker_names(&line.speakers, &registry, &characters),
+            text: markup::plain(&line.text),
+        });
+    }
+}
+
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // After the dialogue has progressed this frame
+        app.init_resource::<DialogueHistory>()
+            .add_system_to_stage(CoreStage::PostUpdate, record_history.system());
+    }
+}
+
+const VISIBLE_ENTRIES: usize = 12;
+
+struct HistoryPanel {
+    /// How many entries the panel is scrolled back from the most recent
+    scroll: usize,
+}
+
+struct HistoryText;
+
+/// H opens and closes the history.
+fn toggle_history_panel(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    panel_query: Query<Entity, With<HistoryPanel>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::H) {
+        return
+    }
+
+    if let Some(panel) = panel_query.iter().next() {
+        commands
+            .entity(panel)
+            .despawn_recursive();
+        return
+    }
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(40.0),
+                    left: Val::Px(40.0),
+                    ..Default::default()
+                },
+                size: Size {
+                    width: Val::Px(1280. - 2. * 40.),
+                    height: Val::Px(720. - 2. * 40.),
+                },
+                ..Default::default()
+            },
+            material: materials.add(Color::rgba(0., 0., 0., 0.85).into()),
+            ..Default::default()
+        })
+        .insert(HistoryPanel { scroll: 0 })
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    style: Style {
+                        align_self: AlignSelf::FlexEnd,
+                        margin: Rect::all(Val::Px(15.0)),
+                        max_size: Size {
+                            width: Val::Px(1280. - 2. * 55.),
+                            height: Val::Undefined,
+                        },
+                        ..Default::default()
+                    },
+                    text: Text {
+                        sections: vec![],
+                        alignment: TextAlignment {
+                            vertical: VerticalAlign::Top,
+                            horizontal: HorizontalAlign::Left,
+                        },
+                    },
+                    ..Default::default()
+                })
+                .insert(HistoryText);
+        });
+}
+
+/// The mouse wheel, up and down scroll through older lines.
+fn scroll_history_panel(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut ev_wheel: EventReader<MouseWheel>,
+    history: Res<DialogueHistory>,
+    mut panel_query: Query<&mut HistoryPanel>,
+) {
+    let mut delta: i64 = 0;
+    for MouseWheel { unit, y, .. } in ev_wheel.iter() {
+        delta += match unit {
+            MouseScrollUnit::Line => *y as i64,
+            MouseScrollUnit::Pixel => (*y / 20.) as i64,
+        };
+    }
+    if keyboard_input.just_pressed(KeyCode::Up) {
+        delta += 1;
+    }
+    if keyboard_input.just_pressed(KeyCode::Down) {
+        delta -= 1;
+    }
+
+    let spoken = history.entries.iter().filter(|entry| !entry.text.is_empty()).count();
+    let max_scroll = spoken.saturating_sub(VISIBLE_ENTRIES) as i64;
+    for mut panel in panel_query.iter_mut() {
+        panel.scroll = (panel.scroll as i64 + delta).clamp(0, max_scroll) as usize;
+    }
+}
+
+fn render_history_panel(
+    history: Res<DialogueHistory>,
+    asset_server: Res<AssetServer>,
+    panel_query: Query<&HistoryPanel>,
+    mut text_query: Query<&mut Text, With<HistoryText>>,
+) {
+    if let (Some(panel), Some(mut text)) = (panel_query.iter().next(), text_query.iter_mut().next()) {
+        let entries: Vec<&HistoryEntry> = history.entries
+            .iter()
+            .filter(|entry| !entry.text.is_empty())
+            .collect();
+        let end = entries.len().saturating_sub(panel.scroll);
+        let start = end.saturating_sub(VISIBLE_ENTRIES);
+
+        text.sections = entries[start..end]
+            .iter()
+            .map(|entry| TextSection {
+                value: format_entry(entry) + "\n",
+                style: TextStyle {
+                    font: asset_server.load("GloriaHallelujah-Regular.ttf"),
+                    font_size: 22.0,
+                    color: Color::WHITE,
+                },
+            })
+            .collect();
+    }
+}
+
+/// E writes the history to transcript.txt while it is open.
+fn export_history(
+    keyboard_input: Res<Input<KeyCode>>,
+    history: Res<DialogueHistory>,
+    panel_query: Query<&HistoryPanel>,
+) {
+    if panel_query.iter().next().is_none() || !keyboard_input.just_pressed(KeyCode::E) {
+        return
+    }
+
+    match std::fs::write("transcript.txt", history.transcript()) {
+        Ok(()) => info!("Exported transcript to transcript.txt"),
+        Err(e) => warn!("Could not export transcript: {}", e),
+    }
+}
+
+pub struct HistoryPanelPlugin;
+
+impl Plugin for HistoryPanelPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system(toggle_history_panel.system())
+            .add_system(scroll_history_panel.system())
+            .add_system(render_history_panel.system())
+            .add_system(export_history.system());
+    }
+}
@@ -0,0 +1,34 @@
+use crate::clock::GameClock;
+use crate::dialogue::Speaking;
+use crate::ghost::GhostInteractionEvent;
+use bevy::prelude::{EventReader, Query, Res, ResMut, With};
+use std::time::Duration;
+
+/// When the player last did anything or a line was last being spoken.
+#[derive(Default)]
+pub struct IdleTimer {
+    last_activity: Duration,
+}
+
+impl IdleTimer {
+    /// How long the player has been left with nothing happening.
+    pub fn idle(&self, now: Duration) -> Duration {
+        now.saturating_sub(self.last_activity)
+    }
+
+    pub fn reset(&mut self, now: Duration) {
+        self.last_activity = now;
+    }
+}
+
+pub(crate) fn track_activity(
+    clock: Res<GameClock>,
+    speaking_query: Query<(), With<Speaking>>,
+    mut ev_interaction: EventReader<GhostInteractionEvent>,
+    mut timer: ResMut<IdleTimer>,
+) {
+    let interacted = ev_interaction.iter().count() > 0;
+    if interacted || speaking_query.iter().next().is_some() {
+        timer.reset(clock.elapsed());
+    }
+}
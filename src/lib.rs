@@ -0,0 +1,26 @@
+pub mod animation;
+pub mod audio;
+pub mod characters;
+pub mod check;
+pub mod clock;
+pub mod concepts;
+pub mod condition;
+pub mod debugger;
+pub mod dialogue;
+pub mod dialogue_display;
+pub mod duration;
+pub mod ghost;
+pub mod graph;
+pub mod history;
+pub mod idle;
+pub mod markup;
+pub mod menu;
+pub mod question_display;
+pub mod registry;
+pub mod room;
+pub mod save;
+pub mod scene;
+pub mod simulation;
+pub mod story;
+pub mod time_scale;
+pub mod variants;
@@ -1,25 +1,22 @@
 use bevy::prelude::{App, EventReader, WindowDescriptor, DefaultPlugins, IntoSystem};
 
-mod audio;
-use audio::AudioPlugin;
-
-mod concepts;
-use concepts::ConceptPlugin;
-
-mod dialogue;
-use dialogue::DialoguePlugin;
-
-mod ghost;
-use ghost::{GhostPlugin, GhostInteractionEvent};
-
-mod room;
-use room::RoomPlugin;
-
-mod animation;
-use animation::AnimationPlugin;
-
-mod question_display;
-use question_display::QuestionDisplayPlugin;
+use seance::audio::AudioPlugin;
+use seance::clock::ClockPlugin;
+use seance::concepts::ConceptPlugin;
+use seance::dialogue::DialoguePlugin;
+use seance::dialogue_display::DialogueDisplayPlugin;
+use seance::ghost::{GhostPlugin, GhostInteractionEvent};
+use seance::history::{HistoryPlugin, HistoryPanelPlugin};
+use seance::registry::RegistryPlugin;
+use seance::room::RoomPlugin;
+use seance::save::SavePlugin;
+use seance::animation::AnimationPlugin;
+use seance::menu::MenuPlugin;
+use seance::question_display::QuestionDisplayPlugin;
+#[cfg(debug_assertions)]
+use seance::debugger::DebuggerPlugin;
+#[cfg(debug_assertions)]
+use seance::time_scale::{time_scale_arg, TimeScalePlugin};
 
 fn ghost_interactions(mut event_reader: EventReader<GhostInteractionEvent>) {
     for GhostInteractionEvent { ghost, target } in event_reader.iter() {
@@ -28,7 +25,8 @@ fn ghost_interactions(mut event_reader: EventReader<GhostInteractionEvent>) {
 }
 
 fn main() {
-    App::build()
+    let mut app = App::build();
+    app
         .insert_resource(WindowDescriptor {
             title: String::from("Seance"),
             width: 1280.,
@@ -36,13 +34,37 @@ fn main() {
             ..Default::default()
         })
         .add_plugins(DefaultPlugins)
+        .add_plugin(ClockPlugin)
+        .add_plugin(RegistryPlugin)
         .add_plugin(AudioPlugin)
         .add_plugin(GhostPlugin)
         .add_plugin(ConceptPlugin)
         .add_plugin(DialoguePlugin)
+        .add_plugin(DialogueDisplayPlugin)
+        .add_plugin(HistoryPlugin)
+        .add_plugin(HistoryPanelPlugin)
         .add_plugin(RoomPlugin)
+        .add_plugin(SavePlugin)
+        .add_plugin(MenuPlugin)
         .add_plugin(AnimationPlugin)
         .add_plugin(QuestionDisplayPlugin)
-        .add_system(ghost_interactions.system())
-        .run();
+        .add_system(ghost_interactions.system());
+
+    // Debug builds can be sped up with `--time-scale <SCALE>`, or F while playing
+    #[cfg(debug_assertions)]
+    match time_scale_arg(std::env::args().skip(1)) {
+        Ok(scale) => {
+            app.add_plugin(TimeScalePlugin { scale: scale.unwrap_or(1.) });
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    }
+
+    // And explain why lines are or aren't spoken with F3
+    #[cfg(debug_assertions)]
+    app.add_plugin(DebuggerPlugin);
+
+    app.run();
 }
@@ -0,0 +1,89 @@
+use bevy::prelude::Color;
+
+/// A run of line text styled the same way.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Span {
+    pub text: String,
+    /// Inside `*asterisks*`, a stage direction rather than speech
+    pub action: bool,
+    /// Inside `[em]...[/em]`, shown in another colour as the font has no bold face
+    pub emphasis: bool,
+    /// Inside `[color=...]...[/color]`
+    pub color: Option<Color>,
+}
+
+/// Split line text into spans by its markup: `*action*`, `[em]emphasis[/em]` and
+/// `[color=red]...[/color]` or `[color=#ff0000]...[/color]`. Anything unrecognised is kept as text.
+pub fn parse(text: &str) -> Vec<Span> {
+    let mut spans: Vec<Span> = vec![];
+    let mut action = false;
+    let mut emphasis = false;
+    let mut colors: Vec<Color> = vec![];
+    let mut current = String::new();
+
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let before = (action, emphasis, colors.last().copied());
+        let mut consumed = c.len_utf8();
+
+        if c == '*' {
+            action = !action;
+        } else if let Some(tag) = tag(rest) {
+            consumed = tag.len();
+            match &tag[1..tag.len() - 1] {
+                "em" => emphasis = true,
+                "/em" => emphasis = false,
+                "/color" => {
+                    colors.pop();
+                }
+                inner => match inner.strip_prefix("color=").and_then(parse_color) {
+                    Some(color) => colors.push(color),
+                    None => current.push_str(tag),
+                },
+            }
+        } else {
+            current.push(c);
+        }
+
+        if (action, emphasis, colors.last().copied()) != before && !current.is_empty() {
+            let (action, emphasis, color) = before;
+            spans.push(Span { text: std::mem::take(&mut current), action, emphasis, color });
+        }
+        rest = &rest[consumed..];
+    }
+
+    if !current.is_empty() {
+        spans.push(Span { text: current, action, emphasis, color: colors.last().copied() });
+    }
+    spans
+}
+
+/// Line text with its markup removed, as it reads on screen.
+pub fn plain(text: &str) -> String {
+    parse(text).into_iter().map(|span| span.text).collect()
+}
+
+/// The `[...]` tag at the start of `text`, if there is one.
+fn tag(text: &str) -> Option<&str> {
+    if !text.starts_with('[') {
+        return None
+    }
+    text.find(']').map(|end| &text[..=end])
+}
+
+fn parse_color(name: &str) -> Option<Color> {
+    if let Some(hex) = name.strip_prefix('#') {
+        return Color::hex(hex).ok()
+    }
+    match name {
+        "white" => Some(Color::WHITE),
+        "black" => Some(Color::BLACK),
+        "grey" | "gray" => Some(Color::GRAY),
+        "red" => Some(Color::RED),
+        "green" => Some(Color::GREEN),
+        "blue" => Some(Color::BLUE),
+        "yellow" => Some(Color::YELLOW),
+        "purple" => Some(Color::PURPLE),
+        _ => None,
+    }
+}
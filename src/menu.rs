@@ -0,0 +1,324 @@
+use crate::audio::Volume;
+use crate::clock::GameClock;
+use crate::dialogue_display::Typewriter;
+use crate::history::DialogueHistory;
+use crate::room::{GameState, Room, ACTS};
+use crate::save::{has_saved_game, read_saved_game, PendingLoad, SaveDir};
+use crate::story::StoryState;
+use bevy::app::AppExit;
+use bevy::prelude::{
+    AlignItems, AppBuilder, AssetServer, Assets, BuildChildren, ButtonBundle, Changed, Color,
+    ColorMaterial, Commands, DespawnRecursiveExt, Entity, EventWriter, FlexDirection, FromWorld,
+    Handle, HorizontalAlign, Input, Interaction, IntoSystem, JustifyContent, KeyCode, NodeBundle,
+    Plugin, PositionType, Query, Rect, Res, ResMut, Size, State, Style, SystemSet, Text,
+    TextAlignment, TextBundle, TextStyle, Val, VerticalAlign, With, World, warn,
+};
+
+/// What a menu button does when clicked.
+#[derive(Clone, Copy, PartialEq)]
+enum MenuAction {
+    Continue,
+    NewGame,
+    Quit,
+    Resume,
+    Settings,
+    RestartAct,
+    QuitToTitle,
+    Volume,
+    TextSpeed,
+    Back,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Page {
+    Title,
+    Pause,
+    Settings,
+}
+
+/// Whichever menu is open.
+struct Menu;
+
+struct MenuButton(MenuAction);
+
+struct MenuMaterials {
+    background: Handle<ColorMaterial>,
+    button: Handle<ColorMaterial>,
+    hovered: Handle<ColorMaterial>,
+}
+
+impl FromWorld for MenuMaterials {
+    fn from_world(world: &mut World) -> MenuMaterials {
+        let mut materials = world.get_resource_mut::<Assets<ColorMaterial>>().unwrap();
+        MenuMaterials {
+            background: materials.add(Color::rgba(0., 0., 0., 0.85).into()),
+            button: materials.add(Color::rgba(0.15, 0.15, 0.15, 0.9).into()),
+            hovered: materials.add(Color::rgba(0.3, 0.3, 0.3, 0.9).into()),
+        }
+    }
+}
+
+/// Speeds the text can be revealed at, in characters per second.
+const TEXT_SPEEDS: [(&str, f32); 3] = [("Slow", 20.), ("Normal", 40.), ("Fast", 80.)];
+
+fn text_speed(typewriter: &Typewriter) -> usize {
+    TEXT_SPEEDS
+        .iter()
+        .position(|(_, speed)| (*speed - typewriter.characters_per_second).abs() < f32::EPSILON)
+        .unwrap_or(1)
+}
+
+fn spawn_menu(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    materials: &MenuMaterials,
+    page: Page,
+    volume: &Volume,
+    typewriter: &Typewriter,
+    save_dir: &SaveDir,
+) {
+    let (title, buttons) = match page {
+        Page::Title => {
+            let mut buttons = vec![];
+            if has_saved_game(save_dir) {
+                buttons.push(("Continue".to_string(), MenuAction::Continue));
+            }
+            buttons.push(("New game".to_string(), MenuAction::NewGame));
+            buttons.push(("Quit".to_string(), MenuAction::Quit));
+            ("Seance", buttons)
+        }
+        Page::Pause => ("Paused", vec![
+            ("Resume".to_string(), MenuAction::Resume),
+            ("Settings".to_string(), MenuAction::Settings),
+            ("Restart act".to_string(), MenuAction::RestartAct),
+            ("Quit to title".to_string(), MenuAction::QuitToTitle),
+        ]),
+        Page::Settings => ("Settings", vec![
+            (format!("Volume: {}%", (volume.0 * 100.).round()), MenuAction::Volume),
+            (format!("Text speed: {}", TEXT_SPEEDS[text_speed(typewriter)].0), MenuAction::TextSpeed),
+            ("Back".to_string(), MenuAction::Back),
+        ]),
+    };
+
+    let text_style = |font_size: f32| TextStyle {
+        font: asset_server.load("GloriaHallelujah-Regular.ttf"),
+        font_size,
+        color: Color::WHITE,
+    };
+    let alignment = TextAlignment {
+        vertical: VerticalAlign::Center,
+        horizontal: HorizontalAlign::Center,
+    };
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                // Columns run upwards, so reverse them to read down the screen
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            material: materials.background.clone(),
+            ..Default::default()
+        })
+        .insert(Menu)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                style: Style {
+                    margin: Rect::all(Val::Px(20.)),
+                    ..Default::default()
+                },
+                text: Text::with_section(title, text_style(60.), alignment),
+                ..Default::default()
+            });
+
+            for (label, action) in buttons {
+                parent
+                    .spawn_bundle(ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(400.), Val::Px(70.)),
+                            margin: Rect::all(Val::Px(8.)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        material: materials.button.clone(),
+                        ..Default::default()
+                    })
+                    .insert(MenuButton(action))
+                    .with_children(|button| {
+                        button.spawn_bundle(TextBundle {
+                            text: Text::with_section(label, text_style(30.), alignment),
+                            ..Default::default()
+                        });
+                    });
+            }
+        });
+}
+
+fn despawn_menus(commands: &mut Commands, menus: &Query<Entity, With<Menu>>) {
+    for menu in menus.iter() {
+        commands.entity(menu).despawn_recursive();
+    }
+}
+
+fn open_title(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    materials: Res<MenuMaterials>,
+    volume: Res<Volume>,
+    typewriter: Res<Typewriter>,
+    save_dir: Res<SaveDir>,
+) {
+    spawn_menu(&mut commands, &asset_server, &materials, Page::Title, &volume, &typewriter, &save_dir);
+}
+
+fn open_pause_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    materials: Res<MenuMaterials>,
+    volume: Res<Volume>,
+    typewriter: Res<Typewriter>,
+    save_dir: Res<SaveDir>,
+    mut clock: ResMut<GameClock>,
+) {
+    clock.pause();
+    spawn_menu(&mut commands, &asset_server, &materials, Page::Pause, &volume, &typewriter, &save_dir);
+}
+
+fn close_menu(mut commands: Commands, menus: Query<Entity, With<Menu>>) {
+    despawn_menus(&mut commands, &menus);
+}
+
+fn close_pause_menu(
+    mut commands: Commands,
+    menus: Query<Entity, With<Menu>>,
+    mut clock: ResMut<GameClock>,
+) {
+    clock.resume();
+    despawn_menus(&mut commands, &menus);
+}
+
+/// Escape pauses an act and resumes it again, and leaves the ending for the title.
+fn escape(keyboard_input: Res<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if !keyboard_input.just_pressed(KeyCode::Escape) {
+        return
+    }
+    let result = match state.current() {
+        GameState::Act(_) => state.push(GameState::Paused),
+        GameState::Paused => state.pop(),
+        GameState::Ending => state.set(GameState::Title),
+        _ => Ok(()),
+    };
+    if let Err(e) = result {
+        warn!("{}", e);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn highlight_buttons(
+    materials: Res<MenuMaterials>,
+    mut buttons: Query<(&Interaction, &mut Handle<ColorMaterial>), (Changed<Interaction>, With<MenuButton>)>,
+) {
+    for (interaction, mut material) in buttons.iter_mut() {
+        *material = match interaction {
+            Interaction::Hovered | Interaction::Clicked => materials.hovered.clone(),
+            Interaction::None => materials.button.clone(),
+        };
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn click_buttons(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    materials: Res<MenuMaterials>,
+    buttons: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    menus: Query<Entity, With<Menu>>,
+    room: Res<Room>,
+    mut state: ResMut<State<GameState>>,
+    mut story: ResMut<StoryState>,
+    mut history: ResMut<DialogueHistory>,
+    mut volume: ResMut<Volume>,
+    mut typewriter: ResMut<Typewriter>,
+    save_dir: Res<SaveDir>,
+    mut ev_exit: EventWriter<AppExit>,
+) {
+    let action = match buttons.iter().find(|(interaction, _)| **interaction == Interaction::Clicked) {
+        Some((_, MenuButton(action))) => *action,
+        None => return,
+    };
+
+    let mut page = None;
+    let result = match action {
+        MenuAction::Continue => match read_saved_game(&save_dir) {
+            Some(save) => {
+                *story = StoryState::default();
+                *history = DialogueHistory::default();
+                let act = save.act.clamp(1, ACTS.len());
+                commands.insert_resource(PendingLoad(save));
+                state.set(GameState::Act(act))
+            }
+            None => Ok(()),
+        },
+        MenuAction::NewGame => {
+            *story = StoryState::default();
+            *history = DialogueHistory::default();
+            commands.remove_resource::<PendingLoad>();
+            state.set(GameState::Act(1))
+        }
+        MenuAction::Quit => {
+            ev_exit.send(AppExit);
+            Ok(())
+        }
+        MenuAction::Resume => state.pop(),
+        MenuAction::Settings | MenuAction::Volume | MenuAction::TextSpeed => {
+            if action == MenuAction::Volume {
+                volume.0 = if volume.0 <= 0. { 1. } else { (volume.0 - 0.25).max(0.) };
+            }
+            if action == MenuAction::TextSpeed {
+                typewriter.characters_per_second = TEXT_SPEEDS[(text_speed(&typewriter) + 1) % TEXT_SPEEDS.len()].1;
+            }
+            page = Some(Page::Settings);
+            Ok(())
+        }
+        MenuAction::Back => {
+            page = Some(Page::Pause);
+            Ok(())
+        }
+        // The story goes back to how it was when the act began
+        MenuAction::RestartAct => {
+            *story = room.story.clone();
+            state.replace(GameState::Act(room.act))
+        }
+        MenuAction::QuitToTitle => state.replace(GameState::Title),
+    };
+    if let Err(e) = result {
+        warn!("{}", e);
+    }
+
+    if let Some(page) = page {
+        despawn_menus(&mut commands, &menus);
+        spawn_menu(&mut commands, &asset_server, &materials, page, &volume, &typewriter, &save_dir);
+    }
+}
+
+/// The title menu, and the pause menu that escape opens over an act. The game clock stands still
+/// while the game is paused, and with it the dialogue, the ghost, animation and audio.
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<MenuMaterials>()
+            .add_system(escape.system())
+            .add_system(highlight_buttons.system())
+            .add_system(click_buttons.system())
+            .add_system_set(SystemSet::on_enter(GameState::Title).with_system(open_title.system()))
+            .add_system_set(SystemSet::on_exit(GameState::Title).with_system(close_menu.system()))
+            .add_system_set(SystemSet::on_enter(GameState::Paused).with_system(open_pause_menu.system()))
+            .add_system_set(SystemSet::on_exit(GameState::Paused).with_system(close_pause_menu.system()));
+    }
+}
@@ -2,7 +2,7 @@ use crate::concepts::{Concept, Evoked};
 
 use bevy::prelude::{
     AlignSelf, AppBuilder, AssetServer, Color, Commands, EventReader,
-    HorizontalAlign, IntoSystem, Plugin, PositionType, Query, Rect, Res, Size, Style, Text,
+    HorizontalAlign, IntoSystem, Plugin, PositionType, Query, Rect, Res, ResMut, Size, Style, Text,
     TextAlignment, TextBundle, TextSection, TextStyle, Val, VerticalAlign,
 };
 
@@ -11,6 +11,10 @@ pub struct QuestionDisplay;
 pub struct SetQuestionEvent(pub String);
 pub struct ClearQuestionEvent;
 
+/// The question being shown, if any.
+#[derive(Default)]
+pub struct CurrentQuestion(pub Option<String>);
+
 fn setup(mut commands: Commands) {
     commands
         .spawn_bundle(TextBundle {
@@ -47,9 +51,11 @@ fn question_system(
     mut ev_set: EventReader<SetQuestionEvent>,
     mut ev_clear: EventReader<ClearQuestionEvent>,
     concept_query: Query<(&Concept, &Evoked)>,
+    mut current: ResMut<CurrentQuestion>,
 ) {
     if let Ok((_, mut text)) = text_query.single_mut() {
         for SetQuestionEvent(q) in ev_set.iter() {
+            current.0 = Some(q.clone());
             text.sections = vec![TextSection {
                 value: q.to_string() + "\n",
                 style: TextStyle {
@@ -61,6 +67,7 @@ fn question_system(
         }
 
         for ClearQuestionEvent in ev_clear.iter() {
+            current.0 = None;
             text.sections = vec![];
         }
 
@@ -88,7 +95,8 @@ pub struct QuestionDisplayPlugin;
 
 impl Plugin for QuestionDisplayPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        app.add_startup_system(setup.system())
+        app.init_resource::<CurrentQuestion>()
+            .add_startup_system(setup.system())
             .add_system(question_system.system())
             .add_event::<SetQuestionEvent>()
             .add_event::<ClearQuestionEvent>();
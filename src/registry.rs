@@ -0,0 +1,76 @@
+use crate::characters::CharacterId;
+use crate::concepts::ConceptId;
+use crate::dialogue::LineId;
+use bevy::prelude::{
+    AppBuilder, CoreStage, Entity, IntoSystem, Plugin, Query, RemovedComponents, ResMut, Added,
+};
+use std::collections::HashMap;
+
+/// Resolves the stable ids used by scene scripts to the entities spawned for them.
+#[derive(Default)]
+pub struct Registry {
+    lines: HashMap<LineId, Entity>,
+    concepts: HashMap<ConceptId, Entity>,
+    characters: HashMap<CharacterId, Entity>,
+}
+
+impl Registry {
+    pub fn line(&self, id: &LineId) -> Option<Entity> {
+        self.lines.get(id).copied()
+    }
+
+    pub fn concept(&self, id: &ConceptId) -> Option<Entity> {
+        self.concepts.get(id).copied()
+    }
+
+    pub fn character(&self, id: &CharacterId) -> Option<Entity> {
+        self.characters.get(id).copied()
+    }
+}
+
+fn register_ids(
+    mut registry: ResMut<Registry>,
+    lines: Query<(Entity, &LineId), Added<LineId>>,
+    concepts: Query<(Entity, &ConceptId), Added<ConceptId>>,
+    characters: Query<(Entity, &CharacterId), Added<CharacterId>>,
+) {
+    for (entity, id) in lines.iter() {
+        registry.lines.insert(id.clone(), entity);
+    }
+    for (entity, id) in concepts.iter() {
+        registry.concepts.insert(id.clone(), entity);
+    }
+    for (entity, id) in characters.iter() {
+        registry.characters.insert(id.clone(), entity);
+    }
+}
+
+fn unregister_ids(
+    mut registry: ResMut<Registry>,
+    removed_lines: RemovedComponents<LineId>,
+    removed_concepts: RemovedComponents<ConceptId>,
+    removed_characters: RemovedComponents<CharacterId>,
+) {
+    for entity in removed_lines.iter() {
+        registry.lines.retain(|_, e| *e != entity);
+    }
+    for entity in removed_concepts.iter() {
+        registry.concepts.retain(|_, e| *e != entity);
+    }
+    for entity in removed_characters.iter() {
+        registry.characters.retain(|_, e| *e != entity);
+    }
+}
+
+pub struct RegistryPlugin;
+
+impl Plugin for RegistryPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // Registered before the update stage so that newly spawned lines resolve in the same frame.
+        // Removals are only seen in the frame they happen, so scenes despawned during the update
+        // stage are unregistered after it.
+        app.init_resource::<Registry>()
+            .add_system_to_stage(CoreStage::PreUpdate, register_ids.system())
+            .add_system_to_stage(CoreStage::PostUpdate, unregister_ids.system());
+    }
+}
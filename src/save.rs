@@ -0,0 +1,382 @@
+use crate::animation::Transitions;
+use crate::audio::{Channel, PlayAudioEvent};
+use crate::clock::GameClock;
+use crate::concepts::{Concept, ConceptId, Evoked};
+use crate::dialogue::{AnimationStartEvent, Interrupted, Line, LineId, LineStartedEvent, Speaking, Spoken, TrackId};
+use crate::ghost::MoveTo;
+use crate::idle::IdleTimer;
+use crate::question_display::{CurrentQuestion, SetQuestionEvent};
+use crate::room::{GameState, Room};
+use crate::story::{StoryState, Value};
+use bevy::app::Events;
+use bevy::prelude::{
+    AppBuilder, CoreStage, Entity, EventReader, ExclusiveSystemDescriptorCoercion,
+    Input, IntoExclusiveSystem, IntoSystem, KeyCode, Name, Plugin, Query, Res, ResMut, State,
+    SystemSet, Transform, With, World, info, warn,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Bumped whenever the save format changes, so that older saves are not misread.
+pub const SAVE_VERSION: u32 = 2;
+
+/// A line that was being spoken when the game was saved.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct SpeakingLine {
+    pub line: LineId,
+    /// How long it had been spoken for
+    pub elapsed: Duration,
+}
+
+/// A line that was cut off and waiting to be resumed when the game was saved.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct InterruptedLine {
+    pub line: LineId,
+    pub at: Duration,
+    pub spoken_for: Duration,
+}
+
+/// Everything needed to carry on a game where it was left. Lines and concepts are saved by
+/// their ids, so a save still loads after the scene is spawned again.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct SaveGame {
+    pub version: u32,
+    pub act: usize,
+    /// The game clock, which every saved time is measured against
+    pub time: Duration,
+    pub spoken: HashMap<LineId, Vec<Duration>>,
+    pub evoked: HashMap<ConceptId, Duration>,
+    pub speaking: Vec<SpeakingLine>,
+    pub interrupted: Vec<InterruptedLine>,
+    pub question: Option<String>,
+    /// The named animation each sprite is playing, by sprite name
+    pub animations: HashMap<String, String>,
+    pub ghost: Option<(f32, f32, f32)>,
+    pub story: HashMap<String, Value>,
+    /// The story as it was when the act began, to go back to on restarting it
+    pub act_story: HashMap<String, Value>,
+}
+
+/// Just enough of a save to tell which version wrote it.
+#[derive(Deserialize)]
+struct SaveVersion {
+    version: u32,
+}
+
+impl SaveGame {
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+
+    pub fn from_ron(text: &str) -> anyhow::Result<SaveGame> {
+        let SaveVersion { version } = ron::de::from_str(text)?;
+        if version != SAVE_VERSION {
+            anyhow::bail!("save is version {}, but only version {} can be loaded", version, SAVE_VERSION);
+        }
+        Ok(ron::de::from_str(text)?)
+    }
+}
+
+/// The directory the game is saved in. It is the platform's per-user data directory unless
+/// inserted before `SavePlugin`, and `None` if that could not be found.
+pub struct SaveDir(pub Option<PathBuf>);
+
+impl Default for SaveDir {
+    fn default() -> SaveDir {
+        let home = || std::env::var_os("HOME").map(PathBuf::from);
+        let data = if cfg!(target_os = "windows") {
+            std::env::var_os("APPDATA").map(PathBuf::from)
+        } else if cfg!(target_os = "macos") {
+            home().map(|home| home.join("Library").join("Application Support"))
+        } else {
+            std::env::var_os("XDG_DATA_HOME")
+                .map(PathBuf::from)
+                .or_else(|| home().map(|home| home.join(".local").join("share")))
+        };
+        SaveDir(data.map(|data| data.join("seance")))
+    }
+}
+
+/// Where the game is saved.
+pub fn save_path(SaveDir(dir): &SaveDir) -> Option<PathBuf> {
+    dir.as_ref().map(|dir| dir.join("save.ron"))
+}
+
+/// Record the state of the game as it is now.
+pub fn capture(world: &mut World) -> SaveGame {
+    let now = world.get_resource::<GameClock>().unwrap().elapsed();
+
+    let spoken = world
+        .query::<(&LineId, &Spoken)>()
+        .iter(world)
+        .map(|(id, Spoken(times))| (id.clone(), times.clone()))
+        .collect();
+    let evoked = world
+        .query_filtered::<(&ConceptId, &Evoked), With<Concept>>()
+        .iter(world)
+        .map(|(id, Evoked(time))| (id.clone(), *time))
+        .collect();
+    let speaking = world
+        .query_filtered::<(&LineId, &Spoken), With<Speaking>>()
+        .iter(world)
+        .filter_map(|(id, Spoken(times))| times.last().map(|started| SpeakingLine {
+            line: id.clone(),
+            elapsed: now.saturating_sub(*started),
+        }))
+        .collect();
+    let interrupted = world
+        .query::<(&LineId, &Interrupted)>()
+        .iter(world)
+        .map(|(id, interrupted)| InterruptedLine {
+            line: id.clone(),
+            at: interrupted.at,
+            spoken_for: interrupted.spoken_for,
+        })
+        .collect();
+    let animations = world
+        .query::<(&Name, &Transitions)>()
+        .iter(world)
+        .filter_map(|(name, transitions)| transitions
+            .current()
+            .map(|current| (name.as_str().to_string(), current.to_string())))
+        .collect();
+    let ghost = world
+        .query_filtered::<&Transform, With<MoveTo>>()
+        .iter(world)
+        .next()
+        .map(|transform| (transform.translation.x, transform.translation.y, transform.translation.z));
+
+    SaveGame {
+        version: SAVE_VERSION,
+        act: world.get_resource::<Room>().map_or(1, |room| room.act),
+        time: now,
+        spoken,
+        evoked,
+        speaking,
+        interrupted,
+        question: world.get_resource::<CurrentQuestion>().and_then(|question| question.0.clone()),
+        animations,
+        ghost,
+        story: world.get_resource::<StoryState>().map(story_values).unwrap_or_default(),
+        act_story: world.get_resource::<Room>().map(|room| story_values(&room.story)).unwrap_or_default(),
+    }
+}
+
+fn story_values(story: &StoryState) -> HashMap<String, Value> {
+    story.values().map(|(name, value)| (name.clone(), value.clone())).collect()
+}
+
+fn send<T: Send + Sync + 'static>(world: &mut World, event: T) {
+    if let Some(mut events) = world.get_resource_mut::<Events<T>>() {
+        events.send(event);
+    }
+}
+
+/// Put a freshly spawned scene back into the state it was saved in.
+pub fn restore(world: &mut World, save: &SaveGame) {
+    world.get_resource_mut::<GameClock>().unwrap().restore(save.time);
+    if let Some(mut idle) = world.get_resource_mut::<IdleTimer>() {
+        idle.reset(save.time);
+    }
+    if let Some(mut story) = world.get_resource_mut::<StoryState>() {
+        for (name, value) in &save.story {
+            story.set(name, value.clone());
+        }
+    }
+    if let Some(mut room) = world.get_resource_mut::<Room>() {
+        room.story = StoryState::default();
+        for (name, value) in &save.act_story {
+            room.story.set(name, value.clone());
+        }
+    }
+
+    let lines: HashMap<LineId, Entity> = world
+        .query::<(Entity, &LineId)>()
+        .iter(world)
+        .map(|(entity, id)| (id.clone(), entity))
+        .collect();
+    let concepts: HashMap<ConceptId, Entity> = world
+        .query::<(Entity, &ConceptId)>()
+        .iter(world)
+        .map(|(entity, id)| (id.clone(), entity))
+        .collect();
+
+    for (id, times) in &save.spoken {
+        match lines.get(id) {
+            Some(entity) => {
+                world.entity_mut(*entity).insert(Spoken(times.clone()));
+            }
+            None => warn!("Saved line {} is not in the scene", id.0),
+        }
+    }
+    for (id, time) in &save.evoked {
+        match concepts.get(id) {
+            Some(entity) => {
+                world.entity_mut(*entity).insert(Evoked(*time));
+            }
+            None => warn!("Saved concept {} is not in the scene", id.0),
+        }
+    }
+    for InterruptedLine { line, at, spoken_for } in &save.interrupted {
+        if let Some(entity) = lines.get(line) {
+            world.entity_mut(*entity).insert(Interrupted { at: *at, spoken_for: *spoken_for });
+        }
+    }
+
+    for SpeakingLine { line, elapsed } in &save.speaking {
+        let entity = match lines.get(line) {
+            Some(entity) => *entity,
+            None => continue,
+        };
+        let (audio, track) = match world.get::<Line>(entity) {
+            Some(line) => (line.audio.clone(), line.track.clone()),
+            None => continue,
+        };
+        // Voice audio cannot be resumed part way through, so voiced lines start again
+        let started = match audio {
+            Some(_) => save.time,
+            None => save.time.saturating_sub(*elapsed),
+        };
+
+        let mut entity = world.entity_mut(entity);
+        match entity.get_mut::<Spoken>() {
+            Some(mut spoken) => {
+                spoken.0.pop();
+                spoken.0.push(started);
+            }
+            None => {
+                entity.insert(Spoken(vec![started]));
+            }
+        }
+        entity.insert(Speaking);
+
+        if let Some(handle) = audio {
+            send(world, PlayAudioEvent { channel: Channel::Dialogue(track), handle });
+        }
+    }
+
+    if let Some(question) = &save.question {
+        send(world, SetQuestionEvent(question.clone()));
+    }
+    for animation in save.animations.values() {
+        send(world, AnimationStartEvent(animation.clone()));
+    }
+    if let Some((x, y, z)) = save.ghost {
+        for mut transform in world.query_filtered::<&mut Transform, With<MoveTo>>().iter_mut(world) {
+            transform.translation.x = x;
+            transform.translation.y = y;
+            transform.translation.z = z;
+        }
+    }
+}
+
+pub fn has_saved_game(save_dir: &SaveDir) -> bool {
+    matches!(save_path(save_dir), Some(path) if path.exists())
+}
+
+/// The saved game, if there is one that can be loaded.
+pub fn read_saved_game(save_dir: &SaveDir) -> Option<SaveGame> {
+    let path = save_path(save_dir)?;
+    let text = std::fs::read_to_string(&path).ok()?;
+    match SaveGame::from_ron(&text) {
+        Ok(save) => {
+            info!("Loading {}", path.display());
+            Some(save)
+        }
+        Err(e) => {
+            warn!("Could not load {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// A saved game to apply once its act has been spawned.
+pub struct PendingLoad(pub SaveGame);
+
+#[derive(Default)]
+struct SaveRequested(bool);
+
+fn apply_pending_load(world: &mut World) {
+    if world.get_resource::<PendingLoad>().is_none() {
+        return
+    }
+    // The act is spawned on entering it, so its entities exist from the following frame
+    match world.get_resource::<State<GameState>>().map(|state| *state.current()) {
+        Some(GameState::Act(_)) => {}
+        _ => return,
+    }
+    if let Some(PendingLoad(save)) = world.remove_resource::<PendingLoad>() {
+        restore(world, &save);
+    }
+}
+
+/// The game is saved whenever a line of the main conversation starts, and on F5.
+fn request_save(
+    keyboard_input: Res<Input<KeyCode>>,
+    state: Res<State<GameState>>,
+    lines: Query<&Line>,
+    mut ev_started: EventReader<LineStartedEvent>,
+    mut requested: ResMut<SaveRequested>,
+) {
+    let started = ev_started
+        .iter()
+        .filter_map(|started| lines.get(started.line).ok())
+        .any(|line| line.track == TrackId::main());
+    if let GameState::Act(_) = state.current() {
+        if started || keyboard_input.just_pressed(KeyCode::F5) {
+            requested.0 = true;
+        }
+    }
+}
+
+fn write_save(world: &mut World) {
+    if !std::mem::take(&mut world.get_resource_mut::<SaveRequested>().unwrap().0) {
+        return
+    }
+    let path = match save_path(world.get_resource::<SaveDir>().unwrap()) {
+        Some(path) => path,
+        None => return warn!("Nowhere to save the game"),
+    };
+
+    let result = capture(world)
+        .to_ron()
+        .map_err(anyhow::Error::from)
+        .and_then(|text| {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            // Written in full before it replaces the last save, so a crash cannot leave half of one
+            let partial = path.with_extension("ron.partial");
+            std::fs::write(&partial, text)?;
+            Ok(std::fs::rename(&partial, &path)?)
+        });
+    match result {
+        Ok(()) => info!("Saved to {}", path.display()),
+        Err(e) => warn!("Could not save to {}: {}", path.display(), e),
+    }
+}
+
+/// A finished game starts again from the beginning.
+fn remove_save(save_dir: Res<SaveDir>) {
+    if let Some(path) = save_path(&save_dir) {
+        if std::fs::remove_file(&path).is_ok() {
+            info!("Removed {}", path.display());
+        }
+    }
+}
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<SaveDir>()
+            .init_resource::<SaveRequested>()
+            // After the scene's ids are registered, before the dialogue moves on
+            .add_system_to_stage(CoreStage::PreUpdate, apply_pending_load.exclusive_system().at_end())
+            .add_system_to_stage(CoreStage::PostUpdate, request_save.system())
+            .add_system_to_stage(CoreStage::Last, write_save.exclusive_system())
+            .add_system_set(SystemSet::on_enter(GameState::Ending).with_system(remove_save.system()));
+    }
+}
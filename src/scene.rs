@@ -0,0 +1,470 @@
+use crate::animation::{animation_bundle, AnimationDefinition, BLINK_ANIMATION, TALK_ANIMATION};
+use crate::characters::{Character, CharacterId};
+use crate::concepts::{Concept, ConceptId, EvokesConcept};
+use crate::condition::Condition;
+use crate::dialogue::{Escalation, Line, LineId, Resume, TrackId};
+use crate::dialogue_display::TextBoxPlacement;
+use crate::duration::AutoDuration;
+use crate::ghost::{ghost_bundle, Clickable};
+use crate::story::Value;
+use crate::variants::{Variant, Variants};
+use bevy::asset::{AssetLoader, BoxedFuture, LoadContext, LoadedAsset};
+use bevy::prelude::{
+    AssetServer, Assets, Color, Commands, Entity, GlobalTransform, Name, SpriteSheetBundle, TextureAtlas,
+    Transform, Vec2, Vec3,
+};
+use bevy::reflect::TypeUuid;
+use bevy::sprite::Rect;
+use bevy_interact_2d::{Group, Interactable};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+/// A complete scene (one act) as written by the script authors.
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "3c0d6f4e-5b0a-4a8e-9f8e-2d51c1f0b7a4"]
+pub struct SceneDefinition {
+    #[serde(default)]
+    pub tracks: Vec<TrackDefinition>,
+    #[serde(default)]
+    pub characters: Vec<CharacterDefinition>,
+    #[serde(default)]
+    pub concepts: Vec<ConceptDefinition>,
+    #[serde(default)]
+    pub sprites: Vec<SpriteDefinition>,
+    #[serde(default)]
+    pub lines: Vec<LineDefinition>,
+}
+
+impl SceneDefinition {
+    pub fn from_bytes(bytes: &[u8]) -> Result<SceneDefinition, ron::Error> {
+        ron::de::from_bytes(bytes)
+    }
+}
+
+/// A dialogue track other than the main one.
+#[derive(Deserialize)]
+pub struct TrackDefinition {
+    pub id: TrackId,
+    pub text_box: TextBoxPlacement,
+}
+
+#[derive(Deserialize)]
+pub struct CharacterDefinition {
+    pub id: CharacterId,
+    pub name: String,
+    /// Colour of the name plate, as (red, green, blue)
+    #[serde(default = "default_color")]
+    pub color: (f32, f32, f32),
+    #[serde(default)]
+    pub talk_animation: Option<String>,
+}
+
+fn default_color() -> (f32, f32, f32) {
+    (1., 1., 1.)
+}
+
+#[derive(Deserialize)]
+pub struct ConceptDefinition {
+    pub id: ConceptId,
+    pub description: String,
+    #[serde(default)]
+    pub parents: Vec<HashSet<ConceptId>>,
+}
+
+#[derive(Deserialize)]
+pub enum AtlasDefinition {
+    Grid {
+        tile_size: (f32, f32),
+        columns: usize,
+        rows: usize,
+    },
+    Rects {
+        size: (f32, f32),
+        rects: Vec<((f32, f32), (f32, f32))>,
+    },
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub enum AnimationKind {
+    Simple,
+    Blink,
+    Talk,
+}
+
+impl From<AnimationKind> for AnimationDefinition {
+    fn from(kind: AnimationKind) -> AnimationDefinition {
+        match kind {
+            AnimationKind::Simple => AnimationDefinition::Simple,
+            AnimationKind::Blink => BLINK_ANIMATION,
+            AnimationKind::Talk => TALK_ANIMATION,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct AnimationFrames {
+    pub kind: AnimationKind,
+    pub frames: Vec<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct AnimationsDefinition {
+    pub default: AnimationFrames,
+    #[serde(default)]
+    pub named: HashMap<String, AnimationFrames>,
+}
+
+#[derive(Deserialize)]
+pub struct BoundingBox {
+    pub min: (f32, f32),
+    pub max: (f32, f32),
+}
+
+#[derive(Deserialize)]
+pub struct SpriteDefinition {
+    pub name: String,
+    pub texture: String,
+    pub atlas: AtlasDefinition,
+    #[serde(default)]
+    pub translation: (f32, f32, f32),
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    #[serde(default)]
+    pub animations: Option<AnimationsDefinition>,
+    #[serde(default)]
+    pub interactable: Option<BoundingBox>,
+    #[serde(default)]
+    pub evokes: Option<ConceptId>,
+    #[serde(default)]
+    pub ghost: bool,
+}
+
+fn default_scale() -> f32 {
+    1.
+}
+
+/// Either a number of seconds or `Auto`, to use the length of the line's audio.
+#[derive(Deserialize, Clone, Copy)]
+#[serde(untagged)]
+pub enum DurationDefinition {
+    Seconds(f32),
+    Auto,
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct LineDefinition {
+    pub id: LineId,
+    pub text: String,
+    /// Alternatives to `text` and `audio`, one of which is chosen each time
+    pub variants: Vec<VariantDefinition>,
+    pub speakers: Vec<CharacterId>,
+    pub track: TrackId,
+    pub priority: i32,
+    pub duration: DurationDefinition,
+    pub audio: Option<String>,
+    pub music: Option<String>,
+    pub stop_audio: bool,
+    pub stop_music: bool,
+    pub repeatable: bool,
+    pub max_repeats: Option<usize>,
+    /// Seconds
+    pub cooldown: f32,
+    pub escalation: Option<Escalation>,
+    /// Seconds
+    pub idle_after: Option<f32>,
+    pub interruptible: bool,
+    pub interrupt_priority: i32,
+    pub resume: Resume,
+    pub responds_to_concepts: Vec<ConceptId>,
+    pub groups: Vec<LineId>,
+    pub animations: Vec<String>,
+    pub question: Option<String>,
+    pub clear_question: bool,
+    pub starts_animations: Vec<String>,
+    pub ends_animations: Vec<String>,
+    pub ends_act: bool,
+    pub requires_concepts: Vec<ConceptId>,
+    pub consumes_concepts: Vec<ConceptId>,
+    pub requires_any_concept: bool,
+    pub consumes_all_concepts: bool,
+    pub requires_spoken: Vec<LineId>,
+    pub conflicts_spoken: Vec<LineId>,
+    pub condition: Option<Condition>,
+    pub sets: HashMap<String, Value>,
+    pub increments: HashMap<String, i64>,
+}
+
+#[derive(Deserialize)]
+pub struct VariantDefinition {
+    pub text: String,
+    #[serde(default)]
+    pub audio: Option<String>,
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+}
+
+fn default_weight() -> f32 {
+    1.
+}
+
+impl Default for LineDefinition {
+    fn default() -> LineDefinition {
+        LineDefinition {
+            id: LineId("".to_string()),
+            text: "".to_string(),
+            variants: vec![],
+            speakers: vec![],
+            track: TrackId::main(),
+            priority: 0,
+            duration: DurationDefinition::Seconds(3.),
+            audio: None,
+            music: None,
+            stop_audio: false,
+            stop_music: false,
+            repeatable: false,
+            max_repeats: None,
+            cooldown: 0.,
+            escalation: None,
+            idle_after: None,
+            interruptible: false,
+            interrupt_priority: 0,
+            resume: Resume::Never,
+            responds_to_concepts: vec![],
+            groups: vec![],
+            animations: vec![],
+            question: None,
+            clear_question: false,
+            starts_animations: vec![],
+            ends_animations: vec![],
+            ends_act: false,
+            requires_concepts: vec![],
+            consumes_concepts: vec![],
+            requires_any_concept: false,
+            consumes_all_concepts: false,
+            requires_spoken: vec![],
+            conflicts_spoken: vec![],
+            condition: None,
+            sets: HashMap::new(),
+            increments: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SceneLoader;
+
+impl AssetLoader for SceneLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let scene = SceneDefinition::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(scene));
+            Ok(())
+        })
+    }
+
+    // Not every RON file is a scene
+    fn extensions(&self) -> &[&str] {
+        &["scene.ron"]
+    }
+}
+
+/// Spawn every track, character, concept, sprite and line of a scene, returning the entities so
+/// that the scene can be despawned again.
+///
+/// Without `texture_atlases` sprites are spawned without anything to draw, which is enough to
+/// interact with them when running headless.
+pub fn spawn_scene(
+    scene: &SceneDefinition,
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    mut texture_atlases: Option<&mut Assets<TextureAtlas>>,
+) -> Vec<Entity> {
+    let mut entities = vec![];
+
+    for track in &scene.tracks {
+        entities.push(commands
+            .spawn()
+            .insert(track.id.clone())
+            .insert(track.text_box.clone())
+            .id());
+    }
+
+    for character in &scene.characters {
+        let (r, g, b) = character.color;
+        entities.push(commands
+            .spawn()
+            .insert(character.id.clone())
+            .insert(Character {
+                name: character.name.clone(),
+                color: Color::rgb(r, g, b),
+                talk_animation: character.talk_animation.clone(),
+            })
+            .id());
+    }
+
+    for concept in &scene.concepts {
+        entities.push(commands
+            .spawn()
+            .insert(concept.id.clone())
+            .insert(Concept {
+                description: concept.description.clone(),
+                parents: concept.parents.clone(),
+            })
+            .id());
+    }
+
+    let click_group = Group(0);
+    for sprite in &scene.sprites {
+        let (x, y, z) = sprite.translation;
+        let transform = Transform::from_xyz(x, y, z) * Transform::from_scale(Vec3::splat(sprite.scale));
+
+        let mut entity = match texture_atlases.as_deref_mut() {
+            Some(texture_atlases) => {
+                let texture = asset_server.load(sprite.texture.as_str());
+                let atlas = match &sprite.atlas {
+                    AtlasDefinition::Grid { tile_size, columns, rows } => TextureAtlas::from_grid(
+                        texture,
+                        Vec2::new(tile_size.0, tile_size.1),
+                        *columns,
+                        *rows,
+                    ),
+                    AtlasDefinition::Rects { size, rects } => {
+                        let mut atlas = TextureAtlas::new_empty(texture, Vec2::new(size.0, size.1));
+                        for (min, max) in rects {
+                            atlas.add_texture(Rect {
+                                min: Vec2::new(min.0, min.1),
+                                max: Vec2::new(max.0, max.1),
+                            });
+                        }
+                        atlas
+                    }
+                };
+
+                commands.spawn_bundle(SpriteSheetBundle {
+                    texture_atlas: texture_atlases.add(atlas),
+                    transform,
+                    ..Default::default()
+                })
+            }
+            None => commands.spawn_bundle((transform, GlobalTransform::default())),
+        };
+        entity.insert(Name::new(sprite.name.clone()));
+
+        if let Some(animations) = &sprite.animations {
+            entity.insert_bundle(animation_bundle(
+                (animations.default.kind.into(), animations.default.frames.clone()),
+                animations.named
+                    .iter()
+                    .map(|(label, a)| (label.clone(), (a.kind.into(), a.frames.clone())))
+                    .collect(),
+            ));
+        }
+
+        if let Some(BoundingBox { min, max }) = &sprite.interactable {
+            entity
+                .insert(Interactable {
+                    bounding_box: (Vec2::new(min.0, min.1), Vec2::new(max.0, max.1)),
+                    groups: vec![click_group],
+                })
+                .insert(Clickable);
+        }
+
+        if let Some(concept) = &sprite.evokes {
+            entity.insert(EvokesConcept(concept.clone()));
+        }
+
+        if sprite.ghost {
+            entity.insert_bundle(ghost_bundle());
+        }
+        entities.push(entity.id());
+    }
+
+    for line in &scene.lines {
+        let mut entity = commands.spawn();
+        let duration = match line.duration {
+            DurationDefinition::Seconds(seconds) => Duration::from_secs_f32(seconds),
+            DurationDefinition::Auto => {
+                entity.insert(AutoDuration);
+                Duration::default()
+            }
+        };
+        if !line.variants.is_empty() {
+            entity.insert(Variants {
+                variants: line.variants
+                    .iter()
+                    .map(|variant| Variant {
+                        text: variant.text.clone(),
+                        audio: variant.audio.as_ref().map(|path| asset_server.load(path.as_str())),
+                        weight: variant.weight,
+                    })
+                    .collect(),
+                duration: match line.duration {
+                    DurationDefinition::Seconds(_) => Some(duration),
+                    DurationDefinition::Auto => None,
+                },
+                last: None,
+            });
+        }
+        entity
+            .insert(line.id.clone())
+            .insert(Line {
+                text: line.text.clone(),
+                speakers: line.speakers.clone(),
+                track: line.track.clone(),
+                priority: line.priority,
+                duration,
+                audio: line.audio.as_ref().map(|path| asset_server.load(path.as_str())),
+                music: line.music.as_ref().map(|path| asset_server.load(path.as_str())),
+                stop_audio: line.stop_audio,
+                stop_music: line.stop_music,
+                repeatable: line.repeatable,
+                max_repeats: line.max_repeats,
+                cooldown: Duration::from_secs_f32(line.cooldown),
+                escalation: line.escalation.clone(),
+                idle_after: line.idle_after.map(Duration::from_secs_f32),
+                interruptible: line.interruptible,
+                interrupt_priority: line.interrupt_priority,
+                resume: line.resume,
+                responds_to_concepts: line.responds_to_concepts.clone(),
+                groups: line.groups.clone(),
+                animations: line.animations.clone(),
+                question: line.question.clone(),
+                clear_question: line.clear_question,
+                starts_animations: line.starts_animations.clone(),
+                ends_animations: line.ends_animations.clone(),
+                ends_act: line.ends_act,
+                requires_concepts: line.requires_concepts.clone(),
+                consumes_concepts: line.consumes_concepts.clone(),
+                requires_any_concept: line.requires_any_concept,
+                consumes_all_concepts: line.consumes_all_concepts,
+                requires_spoken: line.requires_spoken.clone(),
+                conflicts_spoken: line.conflicts_spoken.clone(),
+                condition: line.condition.clone(),
+                sets: line.sets.clone(),
+                increments: line.increments.clone(),
+            });
+        entities.push(entity.id());
+    }
+
+    // Groups that are not lines themselves still need an entity to record when they were spoken
+    let line_ids: HashSet<&LineId> = scene.lines.iter().map(|line| &line.id).collect();
+    let group_ids: HashSet<&LineId> = scene.lines
+        .iter()
+        .flat_map(|line| line.groups.iter())
+        .filter(|id| !line_ids.contains(id))
+        .collect();
+    for id in group_ids {
+        entities.push(commands
+            .spawn()
+            .insert(id.clone())
+            .id());
+    }
+
+    entities
+}
@@ -0,0 +1,151 @@
+use crate::audio::{PlayAudioEvent, StopAudioEvent};
+use crate::clock::GameClock;
+use crate::concepts::ConceptPlugin;
+use crate::dialogue::{ActFinishedEvent, DialoguePlugin};
+use crate::ghost::{GhostInteractionEvent, MoveTo};
+use crate::history::{DialogueHistory, HistoryEntry, HistoryPlugin};
+use crate::question_display::{ClearQuestionEvent, SetQuestionEvent};
+use crate::registry::RegistryPlugin;
+use crate::save::{capture, restore, SaveGame};
+use crate::scene::{spawn_scene, SceneDefinition};
+use crate::story::StoryState;
+use crate::variants::DialogueRng;
+use bevy::app::{App, Events, ManualEventReader};
+use bevy::asset::{AddAsset, AssetPlugin, AssetServer};
+use bevy::core::Name;
+use bevy::ecs::system::CommandQueue;
+use bevy::prelude::{Commands, Entity, MinimalPlugins, With};
+use bevy_kira_audio::AudioSource;
+use std::time::Duration;
+
+/// The ghost interacting with the object named `object` at `time`.
+pub struct ScriptedInteraction {
+    pub time: Duration,
+    pub object: String,
+}
+
+impl ScriptedInteraction {
+    pub fn at(seconds: u64, object: &str) -> ScriptedInteraction {
+        ScriptedInteraction {
+            time: Duration::from_secs(seconds),
+            object: object.to_string(),
+        }
+    }
+}
+
+/// Runs the dialogue and concept systems of a scene without a window or audio device,
+/// stepping the game clock by a fixed amount each frame. Variants are chosen from a fixed seed
+/// so that every run says the same thing.
+pub struct Simulation {
+    app: App,
+    step: Duration,
+    act_finished: Option<Duration>,
+    ev_act_finished: ManualEventReader<ActFinishedEvent>,
+}
+
+impl Simulation {
+    pub fn new(scene: &SceneDefinition) -> Simulation {
+        let mut builder = App::build();
+        builder
+            .add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_asset::<AudioSource>()
+            .init_resource::<GameClock>()
+            .insert_resource(DialogueRng::seeded(0))
+            // Normally registered by plugins that need a window or audio device
+            .add_event::<GhostInteractionEvent>()
+            .add_event::<PlayAudioEvent>()
+            .add_event::<StopAudioEvent>()
+            .add_event::<SetQuestionEvent>()
+            .add_event::<ClearQuestionEvent>()
+            .add_plugin(RegistryPlugin)
+            .add_plugin(ConceptPlugin)
+            .add_plugin(DialoguePlugin)
+            .add_plugin(HistoryPlugin);
+        let mut app = std::mem::take(&mut builder.app);
+
+        let mut queue = CommandQueue::default();
+        {
+            let asset_server = app.world.get_resource::<AssetServer>().unwrap();
+            let mut commands = Commands::new(&mut queue, &app.world);
+            spawn_scene(scene, &mut commands, asset_server, None);
+        }
+        queue.apply(&mut app.world);
+
+        Simulation {
+            app,
+            step: Duration::from_millis(100),
+            act_finished: None,
+            ev_act_finished: ManualEventReader::default(),
+        }
+    }
+
+    /// Play the scene until `end`, performing each interaction once its time is reached.
+    /// Returns every line spoken since the simulation began.
+    pub fn run(&mut self, interactions: &[ScriptedInteraction], end: Duration) -> Vec<HistoryEntry> {
+        let mut pending: Vec<&ScriptedInteraction> = interactions.iter().collect();
+        pending.sort_by_key(|interaction| interaction.time);
+        pending.reverse();
+
+        while self.now() < end {
+            while let Some(interaction) = pending.last() {
+                if interaction.time > self.now() {
+                    break;
+                }
+                self.interact(&interaction.object);
+                pending.pop();
+            }
+
+            self.app.update();
+            let events = self.app.world.get_resource::<Events<ActFinishedEvent>>().unwrap();
+            if self.act_finished.is_none() && self.ev_act_finished.iter(events).next().is_some() {
+                self.act_finished = Some(self.now());
+            }
+            self.app.world.get_resource_mut::<GameClock>().unwrap().advance(self.step);
+        }
+
+        self.app.world.get_resource::<DialogueHistory>().unwrap().entries.clone()
+    }
+
+    /// Save the game as it stands.
+    pub fn save(&mut self) -> SaveGame {
+        capture(&mut self.app.world)
+    }
+
+    /// Carry on from a saved game instead of the beginning.
+    pub fn load(&mut self, save: &SaveGame) {
+        restore(&mut self.app.world, save);
+    }
+
+    pub fn story(&self) -> &StoryState {
+        self.app.world.get_resource::<StoryState>().unwrap()
+    }
+
+    /// When a line ending the act finished, if one has.
+    pub fn act_finished(&self) -> Option<Duration> {
+        self.act_finished
+    }
+
+    pub fn now(&self) -> Duration {
+        self.app.world.get_resource::<GameClock>().unwrap().elapsed()
+    }
+
+    fn interact(&mut self, object: &str) {
+        let world = &mut self.app.world;
+        let ghost = world
+            .query_filtered::<Entity, With<MoveTo>>()
+            .iter(world)
+            .next()
+            .expect("scene has no ghost");
+        let target = world
+            .query::<(Entity, &Name)>()
+            .iter(world)
+            .find(|(_, name)| name.as_str() == object)
+            .map(|(entity, _)| entity)
+            .unwrap_or_else(|| panic!("scene has no object named {}", object));
+        world
+            .get_resource_mut::<Events<GhostInteractionEvent>>()
+            .unwrap()
+            .send(GhostInteractionEvent { ghost, target });
+    }
+}
@@ -0,0 +1,45 @@
+use bevy::prelude::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Value {
+    Bool(bool),
+    Int(i64),
+    Text(String),
+}
+
+/// Named narrative state that lines record and branch on, e.g. "family_trust" or
+/// "margaret_convinced". Anything never set is unset rather than false or zero.
+#[derive(Clone, Default)]
+pub struct StoryState {
+    values: HashMap<String, Value>,
+}
+
+impl StoryState {
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.get(name)
+    }
+
+    pub fn set(&mut self, name: &str, value: Value) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    /// Add to a counter, starting from zero if it is unset.
+    pub fn increment(&mut self, name: &str, amount: i64) {
+        let current = match self.values.get(name) {
+            Some(Value::Int(value)) => *value,
+            None => 0,
+            Some(value) => {
+                warn!("Cannot increment {} as it is {:?}", name, value);
+                return
+            }
+        };
+        self.values.insert(name.to_string(), Value::Int(current + amount));
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.values.iter()
+    }
+}
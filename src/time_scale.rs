@@ -0,0 +1,48 @@
+use crate::clock::GameClock;
+use bevy::prelude::{AppBuilder, Input, IntoSystem, KeyCode, Plugin, Res, ResMut, info};
+
+/// Speeds the fast forward key steps through, as multiples of normal speed.
+const SCALES: [f32; 4] = [1., 2., 8., 32.];
+
+/// The speed given by `--time-scale <SCALE>` on the command line, if any.
+pub fn time_scale_arg(mut args: impl Iterator<Item = String>) -> Result<Option<f32>, String> {
+    while let Some(arg) = args.next() {
+        if arg == "--time-scale" {
+            let value = args.next().ok_or("--time-scale needs a value")?;
+            return match value.parse::<f32>() {
+                Ok(scale) if scale > 0. => Ok(Some(scale)),
+                _ => Err(format!("--time-scale must be a positive number, not {}", value)),
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// F steps through faster speeds and back to normal.
+fn fast_forward(keyboard_input: Res<Input<KeyCode>>, mut clock: ResMut<GameClock>) {
+    if !keyboard_input.just_pressed(KeyCode::F) {
+        return
+    }
+    let scale = SCALES
+        .iter()
+        .copied()
+        .find(|scale| *scale > clock.scale())
+        .unwrap_or(SCALES[0]);
+    info!("Time scale {}x", scale);
+    clock.set_scale(scale);
+}
+
+/// Runs the game clock faster, to get through scripted lines quickly when testing. Dialogue
+/// audio is sped up along with it.
+pub struct TimeScalePlugin {
+    /// The speed to start at
+    pub scale: f32,
+}
+
+impl Plugin for TimeScalePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let scale = self.scale;
+        app.add_startup_system((move |mut clock: ResMut<GameClock>| clock.set_scale(scale)).system())
+            .add_system(fast_forward.system());
+    }
+}
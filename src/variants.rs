@@ -0,0 +1,82 @@
+use crate::dialogue::{Interrupted, Line, Speaking};
+use crate::duration::{AutoDuration, MeasuringAudio};
+use bevy::prelude::{
+    ChangeTrackers, Commands, Entity, Handle, Query, RemovedComponents, ResMut, Without, info,
+};
+use bevy_kira_audio::AudioSource;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::time::Duration;
+
+/// One way of saying a line.
+pub struct Variant {
+    pub text: String,
+    pub audio: Option<Handle<AudioSource>>,
+    pub weight: f32,
+}
+
+/// Alternative wordings of a line. The line's text and audio hold the variant it will say next,
+/// chosen again each time it finishes.
+pub struct Variants {
+    pub variants: Vec<Variant>,
+    /// The line's duration, or `None` to measure each variant as it is chosen
+    pub duration: Option<Duration>,
+    pub last: Option<usize>,
+}
+
+/// Picks between variants, seeded so that a run can be reproduced.
+pub struct DialogueRng(pub StdRng);
+
+impl DialogueRng {
+    pub fn seeded(seed: u64) -> DialogueRng {
+        DialogueRng(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl Default for DialogueRng {
+    fn default() -> DialogueRng {
+        DialogueRng(StdRng::from_entropy())
+    }
+}
+
+/// Choose a variant, avoiding the one said last time when there is a choice.
+fn choose(variants: &Variants, rng: &mut DialogueRng) -> Option<usize> {
+    let candidates: Vec<usize> = (0..variants.variants.len())
+        .filter(|i| variants.variants.len() == 1 || Some(*i) != variants.last)
+        .collect();
+    let weights = candidates.iter().map(|i| variants.variants[*i].weight.max(0.));
+    match WeightedIndex::new(weights) {
+        Ok(distribution) => Some(candidates[distribution.sample(&mut rng.0)]),
+        Err(_) => candidates.first().copied(),
+    }
+}
+
+pub(crate) fn choose_variants(
+    mut lines: Query<(Entity, &mut Line, &mut Variants, ChangeTrackers<Variants>), Without<Interrupted>>,
+    finished: RemovedComponents<Speaking>,
+    mut rng: ResMut<DialogueRng>,
+    mut commands: Commands,
+) {
+    let finished: Vec<Entity> = finished.iter().collect();
+    for (entity, mut line, mut variants, trackers) in lines.iter_mut() {
+        if trackers.is_added() || finished.contains(&entity) {
+            if let Some(index) = choose(&variants, &mut rng) {
+                line.text = variants.variants[index].text.clone();
+                info!("Chose variant {}: {}", index, line.text);
+                line.audio = variants.variants[index].audio.clone();
+                variants.last = Some(index);
+
+                match variants.duration {
+                    Some(duration) => line.duration = duration,
+                    None => {
+                        commands
+                            .entity(entity)
+                            .remove::<MeasuringAudio>()
+                            .insert(AutoDuration);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use common::act1;
+use seance::dialogue::LineId;
+use seance::history::HistoryEntry;
+use seance::simulation::ScriptedInteraction;
+use seance::story::Value;
+use std::time::Duration;
+
+fn spoken(transcript: &[HistoryEntry]) -> Vec<&str> {
+    transcript.iter().map(|entry| entry.line.0.as_str()).collect()
+}
+
+#[test]
+fn act1_plays_through() {
+    let mut act1 = act1();
+    let transcript = act1.run(
+        &[
+            ScriptedInteraction::at(110, "portrait"),
+            ScriptedInteraction::at(140, "music_box"),
+            ScriptedInteraction::at(150, "portrait"),
+            ScriptedInteraction::at(190, "music_box"),
+            ScriptedInteraction::at(270, "rocking_horse"),
+        ],
+        Duration::from_secs(400),
+    );
+    let lines = spoken(&transcript);
+
+    assert_eq!(lines.first(), Some(&"s1_pause"));
+    assert_eq!(lines.last(), Some(&"s1_end"));
+    assert_eq!(lines.iter().filter(|id| **id == "s1_medium_a2_wrong").count(), 1);
+    assert!(!lines.contains(&"s1_jasmine_a4_wrong"));
+
+    let wrong = transcript.iter().find(|entry| entry.line == LineId("s1_medium_a2_wrong".to_string())).unwrap();
+    assert!(wrong.time >= Duration::from_secs(140) && wrong.time < Duration::from_secs(141));
+    assert_eq!(wrong.speaker.as_deref(), Some("Madam Gretchen"));
+
+    let twins = transcript.iter().find(|entry| entry.line == LineId("s1_jasmine_and_melina_a5".to_string())).unwrap();
+    assert_eq!(twins.speaker.as_deref(), Some("Jasmine and Melina"));
+
+    assert_eq!(act1.story().get("family_trust"), Some(&Value::Int(3)));
+    assert_eq!(act1.story().get("margaret_convinced"), Some(&Value::Bool(true)));
+
+    let end = transcript.last().unwrap().time + Duration::from_secs(3);
+    let finished = act1.act_finished().unwrap();
+    assert!(finished >= end && finished < end + Duration::from_secs(1));
+}
+
+#[test]
+fn act1_waits_for_an_answer() {
+    let transcript = act1().run(&[], Duration::from_secs(200));
+    let main: Vec<&str> = spoken(&transcript)
+        .into_iter()
+        .filter(|id| !id.starts_with("s1_twins_whisper"))
+        .collect();
+
+    assert_eq!(main.last(), Some(&"s1_narrator_q1_a"));
+}
+
+#[test]
+fn act1_twins_whisper_over_the_medium() {
+    let transcript = act1().run(&[], Duration::from_secs(200));
+    let time = |id: &str| transcript.iter().find(|entry| entry.line.0 == id).unwrap().time;
+
+    // The whispers start while the medium is still talking on the main track
+    assert!(time("s1_twins_whisper_a") < time("s1_medium_q1_b"));
+    assert!(time("s1_twins_whisper_b") < time("s1_medium_q1_b") + Duration::from_secs(7));
+}
+
+#[test]
+fn act1_wrong_guesses_lead_to_a_hint() {
+    let transcript = act1().run(
+        &[
+            ScriptedInteraction::at(110, "portrait"),
+            ScriptedInteraction::at(140, "music_box"),
+            ScriptedInteraction::at(145, "music_box"),
+            ScriptedInteraction::at(160, "music_box"),
+            ScriptedInteraction::at(175, "music_box"),
+        ],
+        Duration::from_secs(185),
+    );
+    let lines: Vec<&str> = spoken(&transcript)
+        .into_iter()
+        .filter(|id| id.starts_with("s1_medium_a2"))
+        .collect();
+
+    // The second click is within the cooldown so waits for it to pass
+    assert_eq!(lines, vec!["s1_medium_a2_wrong", "s1_medium_a2_wrong", "s1_medium_a2_hint", "s1_medium_a2_hint"]);
+
+    // Repeats say something different each time
+    let wrong: Vec<&str> = transcript
+        .iter()
+        .filter(|entry| entry.line.0 == "s1_medium_a2_wrong")
+        .map(|entry| entry.text.as_str())
+        .collect();
+    assert_ne!(wrong[0], wrong[1]);
+}
+
+#[test]
+fn act1_variants_are_reproducible() {
+    let interactions = [
+        ScriptedInteraction::at(110, "portrait"),
+        ScriptedInteraction::at(140, "music_box"),
+        ScriptedInteraction::at(150, "music_box"),
+    ];
+
+    assert_eq!(
+        act1().run(&interactions, Duration::from_secs(160)),
+        act1().run(&interactions, Duration::from_secs(160)),
+    );
+}
+
+#[test]
+fn act1_hints_when_left_idle() {
+    let transcript = act1().run(
+        &[
+            ScriptedInteraction::at(110, "portrait"),
+            ScriptedInteraction::at(150, "portrait"),
+        ],
+        Duration::from_secs(260),
+    );
+    let hints: Vec<&HistoryEntry> = transcript
+        .iter()
+        .filter(|entry| entry.line.0.starts_with("s1_medium_q3_hint"))
+        .collect();
+
+    assert_eq!(hints.len(), 2);
+    assert_eq!(hints[0].line.0, "s1_medium_q3_hint_a");
+    assert_eq!(hints[1].line.0, "s1_medium_q3_hint_b");
+
+    // Each tier waits for a quiet spell after the last
+    assert!(hints[1].time >= hints[0].time + Duration::from_secs(24));
+}
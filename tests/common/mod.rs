@@ -0,0 +1,159 @@
+// Each test crate only uses some of these
+#![allow(dead_code)]
+
+use bevy::app::{App, AppExit};
+use bevy::asset::{AddAsset, AssetPlugin};
+use bevy::prelude::{
+    ColorMaterial, CoreStage, EventReader, Font, Input, IntoSystem, KeyCode, MinimalPlugins,
+    MouseButton, Mut, ResMut, State, Texture, TextureAtlas,
+};
+use bevy::transform::TransformPlugin;
+use bevy_interact_2d::InteractionState;
+use bevy_kira_audio::AudioSource;
+use seance::animation::AnimationPlugin;
+use seance::audio::{Channel, PlayAudioEvent, StopAudioEvent, Volume};
+use seance::clock::GameClock;
+use seance::concepts::ConceptPlugin;
+use seance::dialogue::DialoguePlugin;
+use seance::dialogue_display::DialogueDisplayPlugin;
+use seance::ghost::GhostInteractionEvent;
+use seance::history::HistoryPlugin;
+use seance::menu::MenuPlugin;
+use seance::question_display::{ClearQuestionEvent, SetQuestionEvent};
+use seance::registry::RegistryPlugin;
+use seance::room::{GameState, RoomPlugin};
+use seance::save::{SaveDir, SavePlugin};
+use seance::scene::SceneDefinition;
+use seance::simulation::Simulation;
+use std::time::Duration;
+
+pub fn act1_scene() -> SceneDefinition {
+    let bytes = std::fs::read("assets/scenes/act1.scene.ron").unwrap();
+    SceneDefinition::from_bytes(&bytes).unwrap()
+}
+
+pub fn act1() -> Simulation {
+    Simulation::new(&act1_scene())
+}
+
+/// A simulation of a scene written inline.
+pub fn simulate(ron: &str) -> Simulation {
+    Simulation::new(&SceneDefinition::from_bytes(ron.as_bytes()).unwrap())
+}
+
+/// The whole game without a window or audio device, stepping the game clock by a tenth of a
+/// second each frame. Audio is only followed as far as which channels were last told to play.
+pub struct Game {
+    pub app: App,
+}
+
+/// Channels told to play and not since told to stop.
+#[derive(Default)]
+pub struct Playing(pub Vec<Channel>);
+
+fn follow_audio(
+    mut playing: ResMut<Playing>,
+    mut ev_play: EventReader<PlayAudioEvent>,
+    mut ev_stop: EventReader<StopAudioEvent>,
+) {
+    for PlayAudioEvent { channel, .. } in ev_play.iter() {
+        playing.0.push(channel.clone());
+    }
+    for StopAudioEvent { channel } in ev_stop.iter() {
+        playing.0.retain(|playing| playing != channel);
+    }
+}
+
+impl Game {
+    /// Start the game, saving in `data` under the temporary directory instead of with the
+    /// player's own saves.
+    pub fn new(data: &str) -> Game {
+        let mut builder = App::build();
+        builder
+            .insert_resource(SaveDir(Some(std::env::temp_dir().join(data))))
+            .add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_plugin(TransformPlugin)
+            .add_asset::<AudioSource>()
+            .add_asset::<ColorMaterial>()
+            .add_asset::<Font>()
+            .add_asset::<Texture>()
+            .add_asset::<TextureAtlas>()
+            .init_resource::<GameClock>()
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<Input<MouseButton>>()
+            .init_resource::<InteractionState>()
+            .init_resource::<Volume>()
+            .init_resource::<Playing>()
+            // Normally registered by plugins that need a window or audio device
+            .add_event::<AppExit>()
+            .add_event::<GhostInteractionEvent>()
+            .add_event::<PlayAudioEvent>()
+            .add_event::<StopAudioEvent>()
+            .add_event::<SetQuestionEvent>()
+            .add_event::<ClearQuestionEvent>()
+            .add_plugin(RegistryPlugin)
+            .add_plugin(ConceptPlugin)
+            .add_plugin(DialoguePlugin)
+            .add_plugin(DialogueDisplayPlugin)
+            .add_plugin(HistoryPlugin)
+            .add_plugin(RoomPlugin)
+            .add_plugin(SavePlugin)
+            .add_plugin(MenuPlugin)
+            .add_plugin(AnimationPlugin)
+            .add_system_to_stage(CoreStage::Last, follow_audio.system());
+        let mut game = Game { app: std::mem::take(&mut builder.app) };
+
+        // Scenes load in the background
+        for _ in 0..500 {
+            if game.state() == GameState::Title {
+                return game
+            }
+            game.update();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("scenes did not load");
+    }
+
+    pub fn update(&mut self) {
+        self.app.update();
+        self.app.world.get_resource_mut::<Input<KeyCode>>().unwrap().update();
+        self.app.world.get_resource_mut::<GameClock>().unwrap().advance(Duration::from_millis(100));
+    }
+
+    /// Play on for `seconds` of game time.
+    pub fn run(&mut self, seconds: u64) {
+        for _ in 0..seconds * 10 {
+            self.update();
+        }
+    }
+
+    pub fn press(&mut self, key: KeyCode) {
+        self.app.world.get_resource_mut::<Input<KeyCode>>().unwrap().press(key);
+        self.update();
+        self.app.world.get_resource_mut::<Input<KeyCode>>().unwrap().release(key);
+    }
+
+    /// Hold `key` down for `seconds` of game time.
+    pub fn hold(&mut self, key: KeyCode, seconds: u64) {
+        self.app.world.get_resource_mut::<Input<KeyCode>>().unwrap().press(key);
+        self.run(seconds);
+        self.app.world.get_resource_mut::<Input<KeyCode>>().unwrap().release(key);
+    }
+
+    pub fn state(&self) -> GameState {
+        *self.app.world.get_resource::<State<GameState>>().unwrap().current()
+    }
+
+    pub fn state_mut(&mut self) -> Mut<'_, State<GameState>> {
+        self.app.world.get_resource_mut::<State<GameState>>().unwrap()
+    }
+
+    pub fn resource<T: Send + Sync + 'static>(&self) -> &T {
+        self.app.world.get_resource::<T>().unwrap()
+    }
+
+    pub fn resource_mut<T: Send + Sync + 'static>(&mut self) -> Mut<'_, T> {
+        self.app.world.get_resource_mut::<T>().unwrap()
+    }
+}
@@ -0,0 +1,24 @@
+use seance::duration::mp3_length;
+
+/// Lengths as kira decodes them.
+const LENGTHS: [(&str, f64); 4] = [
+    ("NAR.S1.Introduction.mp3", 74.031),
+    ("NAR.S1.Q1.mp3", 5.590),
+    ("NAR.S1.Q2.mp3", 13.087),
+    ("NAR.S1.Q4.mp3", 21.211),
+];
+
+#[test]
+fn mp3_length_matches_decoded_audio() {
+    for (file, seconds) in LENGTHS.iter() {
+        let bytes = std::fs::read(format!("assets/dialogue/{}", file)).unwrap();
+        let length = mp3_length(&bytes).unwrap();
+        assert!((length.as_secs_f64() - seconds).abs() < 0.06, "{} measured as {:?}", file, length);
+    }
+}
+
+#[test]
+fn other_bytes_have_no_length() {
+    assert_eq!(mp3_length(b""), None);
+    assert_eq!(mp3_length(b"OggS not an mp3 at all"), None);
+}
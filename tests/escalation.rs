@@ -0,0 +1,39 @@
+mod common;
+
+use common::simulate;
+use seance::simulation::{ScriptedInteraction, Simulation};
+use std::time::Duration;
+
+/// Wrong guesses that lead to a hint, which is only given `max_repeats` times.
+fn scene(max_repeats: usize) -> Simulation {
+    let ron = format!(r#"#![enable(implicit_some)]
+    (
+        concepts: [(id: "crash", description: "Crash")],
+        sprites: [
+            (name: "vase", texture: "vase.png", atlas: Grid(tile_size: (1., 1.), columns: 1, rows: 1), evokes: "crash"),
+            (name: "ghost", texture: "ghost.png", atlas: Grid(tile_size: (1., 1.), columns: 1, rows: 1), ghost: true),
+        ],
+        lines: [
+            (
+                id: "wrong",
+                text: "That is not it.",
+                duration: 2,
+                repeatable: true,
+                requires_any_concept: true,
+                consumes_all_concepts: true,
+                escalation: (after: 1, line: "hint"),
+            ),
+            (id: "hint", text: "Think of the vase.", duration: 2, repeatable: true, max_repeats: {}),
+        ],
+    )"#, max_repeats);
+    simulate(&ron)
+}
+
+#[test]
+fn hint_is_given_no_more_than_it_allows() {
+    let clicks: Vec<ScriptedInteraction> = (1..6).map(|i| ScriptedInteraction::at(i * 5, "vase")).collect();
+    let transcript = scene(2).run(&clicks, Duration::from_secs(30));
+    let lines: Vec<&str> = transcript.iter().map(|entry| entry.line.0.as_str()).collect();
+
+    assert_eq!(lines, vec!["wrong", "hint", "hint"]);
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use bevy::prelude::{Entity, KeyCode, Text};
+use common::{Game, Playing};
+use seance::audio::Channel;
+use seance::dialogue::{Line, LineId, Speaking, TrackId};
+use seance::room::{GameState, Room};
+use seance::save::{capture, PendingLoad};
+use seance::story::{StoryState, Value};
+
+#[test]
+fn continued_act_restarts_from_the_story_before_it() {
+    let mut first = Game::new("seance-test-continue");
+    // As if set in an earlier act
+    first.resource_mut::<StoryState>().set("earlier_act", Value::Bool(true));
+    first.state_mut().set(GameState::Act(1)).unwrap();
+    first.run(10);
+    first.resource_mut::<StoryState>().set("this_act", Value::Bool(true));
+    let save = capture(&mut first.app.world);
+
+    // Continue, as from the title menu
+    let mut second = Game::new("seance-test-continue");
+    second.app.world.insert_resource(PendingLoad(save));
+    second.state_mut().set(GameState::Act(1)).unwrap();
+    second.run(1);
+    assert_eq!(second.resource::<StoryState>().get("this_act"), Some(&Value::Bool(true)));
+
+    let act_story = &second.resource::<Room>().story;
+    assert_eq!(act_story.get("earlier_act"), Some(&Value::Bool(true)));
+    assert_eq!(act_story.get("this_act"), None);
+}
+
+#[test]
+fn quitting_a_paused_act_stops_its_dialogue() {
+    let mut game = Game::new("seance-test-quit");
+    game.state_mut().set(GameState::Act(1)).unwrap();
+    // Part way through the narrator's voiced introduction
+    game.run(10);
+    assert!(game.resource::<Playing>().0.contains(&Channel::Dialogue(TrackId::main())));
+
+    game.press(KeyCode::Escape);
+    assert_eq!(game.state(), GameState::Paused);
+    // As the pause menu's "Quit to title" does
+    game.state_mut().replace(GameState::Title).unwrap();
+    game.run(1);
+
+    assert_eq!(game.state(), GameState::Title);
+    assert!(!game.resource::<Playing>().0.iter().any(|channel| matches!(channel, Channel::Dialogue(_))));
+}
+
+#[test]
+fn fast_forward_stops_at_a_question() {
+    let mut game = Game::new("seance-test-fast-forward");
+    game.state_mut().set(GameState::Act(1)).unwrap();
+    game.run(1);
+    // Long enough to skip every line up to the first question, but not for it to end by itself
+    game.hold(KeyCode::Tab, 5);
+
+    let mut speaking = game.app.world.query_filtered::<(&LineId, &Line), bevy::prelude::With<Speaking>>();
+    let (id, line) = speaking
+        .iter(&game.app.world)
+        .find(|(_, line)| line.track == TrackId::main())
+        .unwrap();
+    assert_eq!(id.0, "s1_medium_q1_b");
+    assert!(line.question.is_some());
+}
+
+#[test]
+fn text_boxes_empty_once_their_lines_end() {
+    let mut game = Game::new("seance-test-text-boxes");
+    game.state_mut().set(GameState::Act(1)).unwrap();
+    game.run(10);
+    let mut texts = game.app.world.query::<(Entity, &Text)>();
+    let (text_box, _) = texts
+        .iter(&game.app.world)
+        .find(|(_, text)| text.sections.iter().any(|section| section.value.starts_with("Narrator")))
+        .unwrap();
+
+    game.press(KeyCode::Escape);
+    game.state_mut().replace(GameState::Title).unwrap();
+    game.run(1);
+
+    assert!(game.app.world.get::<Text>(text_box).unwrap().sections.is_empty());
+}
+
+#[test]
+fn saves_go_in_the_save_dir() {
+    let dir = std::env::temp_dir().join("seance-test-save-dir");
+    let _ = std::fs::remove_file(dir.join("save.ron"));
+
+    let mut game = Game::new("seance-test-save-dir");
+    game.state_mut().set(GameState::Act(1)).unwrap();
+    game.run(1);
+    game.press(KeyCode::F5);
+
+    assert!(dir.join("save.ron").exists());
+    assert!(!dir.join("save.ron.partial").exists());
+}
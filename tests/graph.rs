@@ -0,0 +1,39 @@
+mod common;
+
+use common::act1_scene;
+use seance::graph::dot;
+
+#[test]
+fn every_line_is_a_node() {
+    let scene = act1_scene();
+    let graph = dot(&scene);
+    assert!(graph.starts_with("digraph scene {\n"));
+    assert!(graph.ends_with("}\n"));
+    for line in &scene.lines {
+        assert!(graph.contains(&format!("\"line:{}\" [shape=box, label=", line.id.0)), "{} has no node", line.id.0);
+    }
+}
+
+#[test]
+fn lines_follow_the_lines_they_require() {
+    let graph = dot(&act1_scene());
+    assert!(graph.contains("\"line:s1_pause\" -> \"line:s1_introduction_a\";\n"));
+    assert!(graph.contains("\"line:s1_introduction_a\" -> \"line:s1_introduction_b\";\n"));
+}
+
+#[test]
+fn objects_evoke_concepts_that_lines_need() {
+    let graph = dot(&act1_scene());
+    assert!(graph.contains("\"object:portrait\" -> \"concept:norman\";\n"));
+    assert!(graph.contains("\"object:music_box\" -> \"concept:music_box\";\n"));
+    assert!(graph.contains("\"concept:norman\" -> \"line:"));
+    assert!(graph.contains(" [style=dashed];\n"));
+}
+
+#[test]
+fn labels_are_escaped() {
+    let mut scene = act1_scene();
+    scene.lines[0].text = "She said \"hello\"".to_string();
+    let graph = dot(&scene);
+    assert!(graph.contains("She said \\\"hello\\\""));
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::simulate;
+use seance::dialogue::LineId;
+use seance::history::HistoryEntry;
+use seance::simulation::{ScriptedInteraction, Simulation};
+use std::time::Duration;
+
+fn scene(resume: &str) -> Simulation {
+    scene_with(resume, "")
+}
+
+/// The scene, with more fields given to the interruptible story.
+fn scene_with(resume: &str, story: &str) -> Simulation {
+    let ron = format!(r#"#![enable(implicit_some)]
+    (
+        concepts: [(id: "crash", description: "Crash")],
+        sprites: [
+            (name: "vase", texture: "vase.png", atlas: Grid(tile_size: (1., 1.), columns: 1, rows: 1), evokes: "crash"),
+            (name: "ghost", texture: "ghost.png", atlas: Grid(tile_size: (1., 1.), columns: 1, rows: 1), ghost: true),
+        ],
+        lines: [
+            (
+                id: "story",
+                text: "Once upon a time there was a house full of ghosts.",
+                duration: 10,
+                interruptible: true,
+                interrupt_priority: 5,
+                resume: {},
+                {}
+            ),
+            (id: "ending", text: "The end.", duration: 2, requires_spoken: ["story"]),
+            (id: "reaction", text: "What was that?", priority: 10, duration: 2, requires_concepts: ["crash"], consumes_concepts: ["crash"]),
+        ],
+    )"#, resume, story);
+    simulate(&ron)
+}
+
+fn lines(transcript: &[HistoryEntry]) -> Vec<(&str, u64)> {
+    transcript.iter().map(|entry| (entry.line.0.as_str(), entry.time.as_secs())).collect()
+}
+
+#[test]
+fn reaction_cuts_in() {
+    let transcript = scene("Never").run(&[ScriptedInteraction::at(4, "vase")], Duration::from_secs(20));
+
+    assert_eq!(lines(&transcript), vec![("story", 0), ("reaction", 4), ("ending", 6)]);
+}
+
+#[test]
+fn interrupted_line_restarts() {
+    let transcript = scene("Restart").run(&[ScriptedInteraction::at(4, "vase")], Duration::from_secs(30));
+
+    assert_eq!(lines(&transcript), vec![("story", 0), ("reaction", 4), ("story", 6), ("ending", 16)]);
+}
+
+#[test]
+fn interrupted_line_continues() {
+    let transcript = scene("Continue").run(&[ScriptedInteraction::at(4, "vase")], Duration::from_secs(30));
+
+    // Only what was left of the story is told after the reaction
+    assert_eq!(lines(&transcript), vec![("story", 0), ("reaction", 4), ("story", 6), ("ending", 12)]);
+}
+
+#[test]
+fn interrupted_line_ends_once_resumed() {
+    let mut scene = scene_with("Restart", "ends_act: true,");
+    scene.run(&[ScriptedInteraction::at(4, "vase")], Duration::from_secs(30));
+
+    // Not when the reaction cuts in, but after the story is told again in full
+    assert!(scene.act_finished() >= Some(Duration::from_secs(16)));
+}
+
+#[test]
+fn resumed_line_is_spoken_once() {
+    let mut scene = scene("Continue");
+    scene.run(&[ScriptedInteraction::at(4, "vase")], Duration::from_secs(8));
+
+    // Resuming carries on the same telling, so it counts once towards repeats and cooldowns
+    let save = scene.save();
+    let spoken = &save.spoken[&LineId("story".to_string())];
+    assert_eq!(spoken.len(), 1);
+    // Backdated by the four seconds already told, as if it had never been cut off
+    assert!(spoken[0] < Duration::from_secs(4));
+}
@@ -0,0 +1,37 @@
+use bevy::prelude::Color;
+use seance::markup::{parse, plain, Span};
+
+fn span(text: &str) -> Span {
+    Span { text: text.to_string(), action: false, emphasis: false, color: None }
+}
+
+#[test]
+fn actions_and_emphasis_become_spans() {
+    assert_eq!(
+        parse("It’s [em]really[/em] him. Sniff *Looks down and tears*"),
+        vec![
+            span("It’s "),
+            Span { emphasis: true, ..span("really") },
+            span(" him. Sniff "),
+            Span { action: true, ..span("Looks down and tears") },
+        ],
+    );
+}
+
+#[test]
+fn colours_nest() {
+    assert_eq!(
+        parse("[color=red]a[color=#0000ff]b[/color]c[/color]"),
+        vec![
+            Span { color: Some(Color::RED), ..span("a") },
+            Span { color: Some(Color::hex("0000ff").unwrap()), ..span("b") },
+            Span { color: Some(Color::RED), ..span("c") },
+        ],
+    );
+}
+
+#[test]
+fn unknown_tags_are_kept() {
+    assert_eq!(plain("[i]Hello[/i] *there*"), "[i]Hello[/i] there");
+    assert_eq!(plain("[b]Hello[/b]"), "[b]Hello[/b]");
+}
@@ -0,0 +1,63 @@
+use bevy::app::App;
+use bevy::asset::{AssetPlugin, AssetServer};
+use bevy::ecs::system::CommandQueue;
+use bevy::prelude::{Commands, DespawnRecursiveExt, Entity, IntoSystem, MinimalPlugins, Query, Res, With};
+use seance::concepts::ConceptId;
+use seance::dialogue::LineId;
+use seance::registry::{Registry, RegistryPlugin};
+use seance::scene::{spawn_scene, SceneDefinition};
+
+const SCENE: &str = r#"(
+    concepts: [(id: "crash", description: "Crash")],
+    lines: [(id: "hello", text: "Hello.")],
+)"#;
+
+/// Whether the scene should be despawned, as leaving an act does during the update stage.
+struct Despawn(bool);
+
+fn despawn_scene(
+    mut commands: Commands,
+    despawn: Res<Despawn>,
+    lines: Query<Entity, With<LineId>>,
+    concepts: Query<Entity, With<ConceptId>>,
+) {
+    if despawn.0 {
+        for entity in lines.iter().chain(concepts.iter()) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+#[test]
+fn despawned_scenes_are_unregistered() {
+    let mut builder = App::build();
+    builder
+        .add_plugins(MinimalPlugins)
+        .add_plugin(AssetPlugin)
+        .add_plugin(RegistryPlugin)
+        .insert_resource(Despawn(false))
+        .add_system(despawn_scene.system());
+    let mut app = std::mem::take(&mut builder.app);
+
+    let scene = SceneDefinition::from_bytes(SCENE.as_bytes()).unwrap();
+    let mut queue = CommandQueue::default();
+    {
+        let asset_server = app.world.get_resource::<AssetServer>().unwrap();
+        let mut commands = Commands::new(&mut queue, &app.world);
+        spawn_scene(&scene, &mut commands, asset_server, None);
+    }
+    queue.apply(&mut app.world);
+
+    let hello = LineId("hello".to_string());
+    let crash = ConceptId("crash".to_string());
+    app.update();
+    let registry = app.world.get_resource::<Registry>().unwrap();
+    assert!(registry.line(&hello).is_some());
+    assert!(registry.concept(&crash).is_some());
+
+    app.world.get_resource_mut::<Despawn>().unwrap().0 = true;
+    app.update();
+    let registry = app.world.get_resource::<Registry>().unwrap();
+    assert_eq!(registry.line(&hello), None);
+    assert_eq!(registry.concept(&crash), None);
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::act1;
+use seance::dialogue::LineId;
+use seance::save::{SaveGame, SAVE_VERSION};
+use seance::simulation::ScriptedInteraction;
+use seance::story::Value;
+use std::time::Duration;
+
+#[test]
+fn saved_game_carries_on() {
+    let mut first = act1();
+    first.run(
+        &[
+            ScriptedInteraction::at(110, "portrait"),
+            ScriptedInteraction::at(140, "music_box"),
+            ScriptedInteraction::at(150, "portrait"),
+        ],
+        Duration::from_secs(170),
+    );
+    let save = SaveGame::from_ron(&first.save().to_ron().unwrap()).unwrap();
+    assert_eq!(save.time, Duration::from_secs(170));
+    assert!(save.spoken.contains_key(&LineId("s1_medium_a2".to_string())));
+
+    let mut second = act1();
+    second.load(&save);
+    assert_eq!(second.now(), Duration::from_secs(170));
+    let transcript = second.run(
+        &[
+            ScriptedInteraction::at(190, "music_box"),
+            ScriptedInteraction::at(270, "rocking_horse"),
+        ],
+        Duration::from_secs(400),
+    );
+
+    // Lines spoken before saving are not spoken again
+    assert!(!transcript.iter().any(|entry| entry.line == LineId("s1_medium_a2".to_string())));
+    assert_eq!(transcript.last().map(|entry| entry.line.0.as_str()), Some("s1_end"));
+    assert_eq!(second.story().get("family_trust"), Some(&Value::Int(3)));
+}
+
+#[test]
+fn other_versions_are_not_loaded() {
+    let mut save = act1().save();
+    save.version = SAVE_VERSION + 1;
+    assert!(SaveGame::from_ron(&save.to_ron().unwrap()).is_err());
+}
@@ -0,0 +1,14 @@
+use seance::time_scale::time_scale_arg;
+
+fn args(args: &[&str]) -> impl Iterator<Item = String> {
+    args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>().into_iter()
+}
+
+#[test]
+fn time_scale_from_the_command_line() {
+    assert_eq!(time_scale_arg(args(&[])), Ok(None));
+    assert_eq!(time_scale_arg(args(&["--time-scale", "8"])), Ok(Some(8.)));
+    assert!(time_scale_arg(args(&["--time-scale"])).is_err());
+    assert!(time_scale_arg(args(&["--time-scale", "0"])).is_err());
+    assert!(time_scale_arg(args(&["--time-scale", "fast"])).is_err());
+}